@@ -1,6 +1,9 @@
-use crate::commands::{chat, config, model, setup};
+use crate::commands::{chat, config, explain, fix, init, model, setup, translate, undo};
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use clap::error::{ContextKind, ContextValue, ErrorKind};
+use clap::{CommandFactory, Parser, Subcommand};
+use std::ffi::OsString;
+use std::process::ExitCode;
 
 #[derive(Parser)]
 #[command(
@@ -12,6 +15,9 @@ pub struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
 
+    #[command(flatten)]
+    options: chat::ChatOptions,
+
     /// The question to ask the selected provider (if no subcommand is provided)
     #[arg(trailing_var_arg = true)]
     question: Vec<String>,
@@ -27,23 +33,175 @@ enum Commands {
 
     /// Change the default model
     Model,
+
+    /// Explain what a shell command does, segment by segment
+    Explain {
+        /// The command to explain (quote it to keep pipes and redirections intact)
+        #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        command: Vec<String>,
+    },
+
+    /// Rewrite a command or short script for another shell
+    Translate {
+        /// The shell to translate to
+        #[arg(long, value_enum)]
+        to: translate::Dialect,
+
+        /// The shell the command is written for
+        #[arg(long, value_enum, default_value = "bash")]
+        from: translate::Dialect,
+
+        /// The command to translate (quote it to keep pipes and redirections intact)
+        #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        command: Vec<String>,
+    },
+
+    /// Suggest a corrected version of the last failed command
+    Fix {
+        /// The command that failed (defaults to the one recorded by `tt init`)
+        #[arg(long)]
+        command: Option<String>,
+
+        /// Its exit status (defaults to the one recorded by `tt init`)
+        #[arg(long)]
+        status: Option<i32>,
+
+        /// Error output of the failed run; read from stdin when piped
+        #[arg(long)]
+        stderr: Option<String>,
+
+        #[command(flatten)]
+        options: chat::ChatOptions,
+    },
+
+    /// Suggest a command that reverses the last one `tt` ran
+    Undo {
+        #[command(flatten)]
+        options: chat::ChatOptions,
+    },
+
+    /// Print shell integration to eval from your rc file
+    Init {
+        #[arg(value_enum)]
+        shell: init::InitShell,
+    },
 }
 
-pub async fn run() -> Result<()> {
-    let cli = Cli::parse();
+/// Subcommands whose names also start ordinary questions, such as
+/// `tt undo the last git commit`.
+const QUESTION_WORDS: &[&str] = &["undo", "fix", "init"];
+
+/// Parses `args`, reading a line that starts with `undo`, `fix` or `init`
+/// but has positional words the subcommand does not take as a question.
+fn parse(args: Vec<OsString>) -> Cli {
+    let error = match Cli::try_parse_from(&args) {
+        Ok(cli) => return cli,
+        Err(error) => error,
+    };
+    let Some(position) = args
+        .iter()
+        .skip(1)
+        .position(|arg| QUESTION_WORDS.iter().any(|word| arg == word))
+        .map(|index| index + 1)
+    else {
+        error.exit()
+    };
+
+    let stray_word = match error.kind() {
+        ErrorKind::UnknownArgument => matches!(
+            error.get(ContextKind::InvalidArg),
+            Some(ContextValue::String(arg)) if !arg.starts_with('-')
+        ),
+        // `tt init tcsh` is still a mistake; `tt init a new repo` is not.
+        ErrorKind::InvalidValue => args.len() > position + 2,
+        _ => false,
+    };
+    if !stray_word {
+        error.exit()
+    }
+
+    let mut question = args;
+    question.insert(position, OsString::from("--"));
+    Cli::parse_from(question)
+}
+
+pub async fn run() -> Result<ExitCode> {
+    let cli = parse(std::env::args_os().collect());
+    // Options before a subcommand belong to `tt` itself and would be dropped.
+    if cli.command.is_some() && cli.options != chat::ChatOptions::default() {
+        Cli::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "options such as --run go after the subcommand, as in `tt fix --run`",
+            )
+            .exit();
+    }
 
     match cli.command {
         Some(Commands::Setup) => setup::run().await?,
         Some(Commands::Config) => config::show()?,
         Some(Commands::Model) => model::change().await?,
+        Some(Commands::Explain { command }) => explain::run(&command.join(" ")).await?,
+        Some(Commands::Translate { to, from, command }) => {
+            translate::run(&command.join(" "), from, to).await?
+        }
+        Some(Commands::Fix {
+            command,
+            status,
+            stderr,
+            options,
+        }) => return fix::run(command, status, stderr, &options).await,
+        Some(Commands::Undo { options }) => return undo::run(&options).await,
+        Some(Commands::Init { shell }) => init::run(shell)?,
         None => {
             if cli.question.is_empty() {
                 anyhow::bail!("Please provide a question or run 'tt setup' to configure.");
             }
             let question = cli.question.join(" ");
-            chat::run(&question).await?;
+            return chat::run(&question, &cli.options).await;
         }
     }
 
-    Ok(())
+    Ok(ExitCode::SUCCESS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn question(args: &[&str]) -> Option<String> {
+        let args = std::iter::once("tt").chain(args.iter().copied());
+        let cli = parse(args.map(OsString::from).collect());
+        cli.command.is_none().then(|| cli.question.join(" "))
+    }
+
+    #[test]
+    fn hyphenated_words_stay_in_the_question() {
+        assert_eq!(
+            question(&["what", "does", "rm", "-rf", "do"]).as_deref(),
+            Some("what does rm -rf do")
+        );
+        assert_eq!(
+            question(&["--run", "list", "files", "--retry"]).as_deref(),
+            Some("list files --retry")
+        );
+    }
+
+    #[test]
+    fn subcommand_names_can_start_a_question() {
+        assert_eq!(
+            question(&["undo", "the", "last", "git", "commit"]).as_deref(),
+            Some("undo the last git commit")
+        );
+        assert_eq!(
+            question(&["--run", "fix", "permissions", "on", "./bin"]).as_deref(),
+            Some("fix permissions on ./bin")
+        );
+        assert_eq!(
+            question(&["init", "a", "new", "repo"]).as_deref(),
+            Some("init a new repo")
+        );
+        assert_eq!(question(&["undo", "--run"]), None);
+        assert_eq!(question(&["init", "zsh"]), None);
+    }
 }
@@ -0,0 +1,286 @@
+use crate::shell::{self, Command, List, MAX_NESTING, SimpleCommand};
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::path::Path;
+
+/// Words the POSIX parser leaves in front of the command they introduce:
+/// `time`, and fish's `and`, `or`, `not` and `begin`.
+const LEADING_KEYWORDS: &[&str] = &["time", "and", "or", "not", "begin"];
+
+/// Fish reserved words that start or end a construct rather than a command.
+const CONSTRUCT_KEYWORDS: &[&str] = &["end", "switch", "case"];
+
+/// Builtins of sh, bash, zsh, and fish. A name that is a builtin in any of
+/// them is never reported, since the user may switch shells.
+const BUILTINS: &[&str] = &[
+    ".",
+    ":",
+    "[",
+    "alias",
+    "autoload",
+    "bg",
+    "bind",
+    "bindkey",
+    "break",
+    "builtin",
+    "caller",
+    "cd",
+    "command",
+    "compgen",
+    "complete",
+    "continue",
+    "contains",
+    "count",
+    "declare",
+    "dirs",
+    "disown",
+    "echo",
+    "emulate",
+    "enable",
+    "eval",
+    "exec",
+    "exit",
+    "export",
+    "false",
+    "fc",
+    "fg",
+    "functions",
+    "getopts",
+    "hash",
+    "help",
+    "history",
+    "jobs",
+    "kill",
+    "let",
+    "local",
+    "logout",
+    "mapfile",
+    "math",
+    "noglob",
+    "popd",
+    "print",
+    "printf",
+    "pushd",
+    "pwd",
+    "read",
+    "readarray",
+    "readonly",
+    "rehash",
+    "return",
+    "set",
+    "setopt",
+    "shift",
+    "shopt",
+    "source",
+    "status",
+    "string",
+    "suspend",
+    "test",
+    "times",
+    "trap",
+    "true",
+    "type",
+    "typeset",
+    "ulimit",
+    "umask",
+    "unalias",
+    "unset",
+    "unsetopt",
+    "wait",
+    "whence",
+    "where",
+    "zle",
+    "zmodload",
+];
+
+/// Executables the command would invoke, in order of first appearance.
+/// Builtins, keywords, functions defined inline, and dynamic names such as
+/// `$EDITOR` are left out.
+pub fn invoked_programs(command: &str) -> Vec<String> {
+    let list = shell::parse_partial(command);
+    let mut functions = HashSet::new();
+    collect_functions(&list, &mut functions);
+    let mut programs = Vec::new();
+    collect_programs(&list, 0, &functions, &mut programs);
+
+    let mut seen = HashSet::new();
+    programs.retain(|program| seen.insert(program.clone()));
+    programs
+}
+
+/// Programs from [`invoked_programs`] that cannot be found on `$PATH`.
+pub fn missing_programs(command: &str) -> Vec<String> {
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    missing_programs_in(command, &path)
+}
+
+pub fn missing_programs_in(command: &str, path: &OsStr) -> Vec<String> {
+    invoked_programs(command)
+        .into_iter()
+        .filter(|program| !is_on_path(program, path))
+        .collect()
+}
+
+pub fn is_on_path(program: &str, path: &OsStr) -> bool {
+    std::env::split_paths(path).any(|dir| {
+        if is_executable(&dir.join(program)) {
+            return true;
+        }
+        cfg!(windows)
+            && [".exe", ".cmd", ".bat", ".com", ".ps1"]
+                .iter()
+                .any(|ext| is_executable(&dir.join(format!("{program}{ext}"))))
+    })
+}
+
+/// Names of the executables in the directories on `path`, sorted.
+pub fn path_executables(path: &OsStr) -> Vec<String> {
+    let mut names = Vec::new();
+    for dir in std::env::split_paths(path) {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            if let Ok(name) = entry.file_name().into_string() {
+                if is_executable(&entry.path()) {
+                    names.push(name);
+                }
+            }
+        }
+    }
+    names.sort();
+    names.dedup();
+    names
+}
+
+fn is_executable(path: &Path) -> bool {
+    let Ok(metadata) = path.metadata() else {
+        return false;
+    };
+    if !metadata.is_file() {
+        return false;
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        metadata.permissions().mode() & 0o111 != 0
+    }
+    #[cfg(not(unix))]
+    {
+        true
+    }
+}
+
+fn collect_programs(
+    list: &List,
+    depth: usize,
+    functions: &HashSet<String>,
+    programs: &mut Vec<String>,
+) {
+    if depth > MAX_NESTING {
+        return;
+    }
+
+    for stage in list.stages() {
+        collect_from_command(stage.command, depth, functions, programs);
+    }
+    for substitution in list.substitutions() {
+        collect_programs(&substitution.body, depth + 1, functions, programs);
+    }
+}
+
+fn collect_source(
+    source: &str,
+    depth: usize,
+    functions: &HashSet<String>,
+    programs: &mut Vec<String>,
+) {
+    collect_programs(&shell::parse_partial(source), depth, functions, programs);
+}
+
+fn collect_from_command(
+    command: &SimpleCommand,
+    depth: usize,
+    functions: &HashSet<String>,
+    programs: &mut Vec<String>,
+) {
+    let mut argv = command.words.as_slice();
+    while argv
+        .first()
+        .is_some_and(|word| !word.is_quoted() && LEADING_KEYWORDS.contains(&word.text.as_str()))
+    {
+        argv = &argv[1..];
+    }
+
+    let Some(first) = argv.first() else {
+        return;
+    };
+    if CONSTRUCT_KEYWORDS.contains(&first.text.as_str()) {
+        return;
+    }
+
+    let (wrappers, args) = shell::unwrap_wrappers(argv);
+    // `unwrap_wrappers` lowercases; the original spelling is what PATH holds.
+    for word in &argv[..argv.len() - args.len()] {
+        if wrappers.contains(&shell::program_name(&word.text)) && is_checkable(&word.text) {
+            programs.push(word.text.clone());
+        }
+    }
+
+    let Some(program) = args.first() else {
+        return;
+    };
+    if !is_checkable(&program.raw)
+        || BUILTINS.contains(&program.text.as_str())
+        || functions.contains(&program.text)
+    {
+        return;
+    }
+    programs.push(program.text.clone());
+
+    let name = shell::program_name(&program.text);
+    if matches!(
+        name.as_str(),
+        "sh" | "bash" | "zsh" | "dash" | "ksh" | "fish"
+    ) {
+        if let Some(index) = args.iter().position(|word| word.text == "-c") {
+            if let Some(script) = args.get(index + 1) {
+                collect_source(&script.text, depth + 1, functions, programs);
+            }
+        }
+    }
+    if name == "find" {
+        if let Some(start) = args
+            .iter()
+            .position(|word| matches!(word.text.as_str(), "-exec" | "-execdir" | "-ok"))
+        {
+            let inner: Vec<&str> = args[start + 1..]
+                .iter()
+                .map(|word| word.raw.as_str())
+                .take_while(|raw| !matches!(*raw, ";" | "\\;" | "';'" | "+"))
+                .collect();
+            collect_source(&inner.join(" "), depth + 1, functions, programs);
+        }
+    }
+}
+
+/// Paths and names built from expansions cannot be checked statically.
+fn is_checkable(raw: &str) -> bool {
+    !raw.is_empty()
+        && !raw.starts_with('-')
+        && !raw.contains(['/', '\\', '$', '`', '=', '*', '?', '[', '~'])
+}
+
+/// Names declared as `name() { … }` or `function name` in the command itself,
+/// including inside substitutions.
+fn collect_functions(list: &List, functions: &mut HashSet<String>) {
+    for command in list.commands() {
+        if let Command::Function(function) = command {
+            functions.insert(function.name.clone());
+        }
+    }
+    for substitution in list.substitutions() {
+        collect_functions(&substitution.body, functions);
+    }
+}
@@ -97,6 +97,44 @@ enum StreamEvent {
     Error { error: serde_json::Value },
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    User,
+    Assistant,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::User => "user",
+            Role::Assistant => "assistant",
+        }
+    }
+}
+
+/// One turn of a conversation with the provider.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub role: Role,
+    pub content: String,
+}
+
+impl Message {
+    pub fn user(content: impl Into<String>) -> Self {
+        Self {
+            role: Role::User,
+            content: content.into(),
+        }
+    }
+
+    pub fn assistant(content: impl Into<String>) -> Self {
+        Self {
+            role: Role::Assistant,
+            content: content.into(),
+        }
+    }
+}
+
 pub struct ModelClient {
     provider: ProviderKind,
     api_key: Option<String>,
@@ -118,32 +156,48 @@ impl ModelClient {
         }
     }
 
-    #[cfg(coverage)]
     pub async fn ask_stream(
         &self,
         question: &str,
+        model: &str,
+        system_prompt: &str,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<String>> + Send>>> {
+        self.chat_stream(&[Message::user(question)], model, system_prompt)
+            .await
+    }
+
+    #[cfg(coverage)]
+    pub async fn chat_stream(
+        &self,
+        messages: &[Message],
         _model: &str,
         _system_prompt: &str,
     ) -> Result<Pin<Box<dyn Stream<Item = Result<String>> + Send>>> {
+        let question = messages
+            .last()
+            .map(|message| message.content.as_str())
+            .unwrap_or_default();
         let snippet = format!("(coverage stub) {question}");
         let stream = stream::iter(vec![Ok(snippet)]);
         Ok(Box::pin(stream))
     }
 
+    /// Streams the reply to a conversation; `messages` alternate between user
+    /// and assistant turns and end with a user turn.
     #[cfg(not(coverage))]
-    pub async fn ask_stream(
+    pub async fn chat_stream(
         &self,
-        question: &str,
+        messages: &[Message],
         model: &str,
         system_prompt: &str,
     ) -> Result<Pin<Box<dyn Stream<Item = Result<String>> + Send>>> {
         let response = match self.provider {
             ProviderKind::Anthropic => {
-                self.send_anthropic_request(question, model, system_prompt)
+                self.send_anthropic_request(messages, model, system_prompt)
                     .await?
             }
             ProviderKind::OpenAi | ProviderKind::OpenRouter | ProviderKind::LmStudio => {
-                self.send_openai_request(question, model, system_prompt)
+                self.send_openai_request(messages, model, system_prompt)
                     .await?
             }
         };
@@ -174,7 +228,7 @@ impl ModelClient {
     #[cfg(not(coverage))]
     async fn send_anthropic_request(
         &self,
-        question: &str,
+        messages: &[Message],
         model: &str,
         system_prompt: &str,
     ) -> Result<reqwest::Response> {
@@ -187,10 +241,7 @@ impl ModelClient {
             model: model.to_string(),
             max_tokens: 4096,
             system: system_prompt.to_string(),
-            messages: vec![AnthropicMessage {
-                role: "user".to_string(),
-                content: question.to_string(),
-            }],
+            messages: anthropic_messages(messages),
             stream: true,
         };
 
@@ -208,24 +259,13 @@ impl ModelClient {
     #[cfg(not(coverage))]
     async fn send_openai_request(
         &self,
-        question: &str,
+        messages: &[Message],
         model: &str,
         system_prompt: &str,
     ) -> Result<reqwest::Response> {
-        let messages = vec![
-            OpenAiMessage {
-                role: "system".to_string(),
-                content: system_prompt.to_string(),
-            },
-            OpenAiMessage {
-                role: "user".to_string(),
-                content: question.to_string(),
-            },
-        ];
-
         let request = OpenAiRequest {
             model: model.to_string(),
-            messages,
+            messages: openai_messages(system_prompt, messages),
             stream: true,
             max_tokens: 4096,
             temperature: 0.2,
@@ -255,6 +295,31 @@ impl ModelClient {
     }
 }
 
+#[cfg(not(coverage))]
+fn anthropic_messages(messages: &[Message]) -> Vec<AnthropicMessage> {
+    messages
+        .iter()
+        .map(|message| AnthropicMessage {
+            role: message.role.as_str().to_string(),
+            content: message.content.clone(),
+        })
+        .collect()
+}
+
+/// OpenAI-compatible APIs take the system prompt as the first message.
+#[cfg(not(coverage))]
+fn openai_messages(system_prompt: &str, messages: &[Message]) -> Vec<OpenAiMessage> {
+    let mut converted = vec![OpenAiMessage {
+        role: "system".to_string(),
+        content: system_prompt.to_string(),
+    }];
+    converted.extend(messages.iter().map(|message| OpenAiMessage {
+        role: message.role.as_str().to_string(),
+        content: message.content.clone(),
+    }));
+    converted
+}
+
 #[cfg(not(coverage))]
 #[derive(Default)]
 struct SseDecoder {
@@ -481,6 +546,28 @@ mod tests {
         assert_eq!(events[0].data, "line one\nline two");
     }
 
+    #[test]
+    fn conversation_history_is_sent_in_order() {
+        let history = [
+            Message::user("list big files"),
+            Message::assistant("`du -ah . | sort -h`"),
+            Message::user("It failed with exit code 1."),
+        ];
+
+        let anthropic = anthropic_messages(&history);
+        let roles: Vec<&str> = anthropic
+            .iter()
+            .map(|message| message.role.as_str())
+            .collect();
+        assert_eq!(roles, ["user", "assistant", "user"]);
+
+        let openai = openai_messages("be brief", &history);
+        let roles: Vec<&str> = openai.iter().map(|message| message.role.as_str()).collect();
+        assert_eq!(roles, ["system", "user", "assistant", "user"]);
+        assert_eq!(openai[0].content, "be brief");
+        assert_eq!(openai[2].content, "`du -ah . | sort -h`");
+    }
+
     #[test]
     fn interpret_skips_ping_and_done_events() {
         let ping = SseEvent {
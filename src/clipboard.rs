@@ -0,0 +1,89 @@
+use anyhow::{Context, Result};
+use std::io::Write;
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+// GNU screen truncates DCS strings longer than this.
+const SCREEN_CHUNK: usize = 768;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Multiplexer {
+    None,
+    Tmux,
+    Screen,
+}
+
+impl Multiplexer {
+    pub fn detect() -> Self {
+        if std::env::var_os("TMUX").is_some_and(|value| !value.is_empty()) {
+            return Multiplexer::Tmux;
+        }
+        let term = std::env::var("TERM").unwrap_or_default();
+        if term.starts_with("screen") || std::env::var_os("STY").is_some() {
+            return Multiplexer::Screen;
+        }
+        Multiplexer::None
+    }
+}
+
+/// Builds the OSC 52 "set clipboard" sequence, wrapped for passthrough when
+/// running inside tmux or screen.
+pub fn osc52_sequence(text: &str, multiplexer: Multiplexer) -> String {
+    let osc = format!("\x1b]52;c;{}\x07", base64_encode(text.as_bytes()));
+
+    match multiplexer {
+        Multiplexer::None => osc,
+        Multiplexer::Tmux => format!("\x1bPtmux;{}\x1b\\", osc.replace('\x1b', "\x1b\x1b")),
+        Multiplexer::Screen => osc
+            .as_bytes()
+            .chunks(SCREEN_CHUNK)
+            .map(|chunk| format!("\x1bP{}\x1b\\", String::from_utf8_lossy(chunk)))
+            .collect(),
+    }
+}
+
+/// Writes `text` to the terminal clipboard. The sequence goes to the
+/// controlling terminal when there is one, so it still works when stdout is
+/// piped.
+pub fn copy(text: &str) -> Result<()> {
+    let sequence = osc52_sequence(text, Multiplexer::detect());
+
+    #[cfg(unix)]
+    if let Ok(mut tty) = std::fs::OpenOptions::new().write(true).open("/dev/tty") {
+        tty.write_all(sequence.as_bytes())
+            .and_then(|_| tty.flush())
+            .context("Failed to write clipboard sequence to the terminal")?;
+        return Ok(());
+    }
+
+    let mut out = std::io::stdout();
+    out.write_all(sequence.as_bytes())
+        .and_then(|_| out.flush())
+        .context("Failed to write clipboard sequence to the terminal")
+}
+
+pub fn base64_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let b0 = chunk[0] as u32;
+        let b1 = chunk.get(1).copied().unwrap_or(0) as u32;
+        let b2 = chunk.get(2).copied().unwrap_or(0) as u32;
+        let triple = (b0 << 16) | (b1 << 8) | b2;
+
+        encoded.push(BASE64_ALPHABET[(triple >> 18) as usize & 0x3f] as char);
+        encoded.push(BASE64_ALPHABET[(triple >> 12) as usize & 0x3f] as char);
+        if chunk.len() > 1 {
+            encoded.push(BASE64_ALPHABET[(triple >> 6) as usize & 0x3f] as char);
+        } else {
+            encoded.push('=');
+        }
+        if chunk.len() > 2 {
+            encoded.push(BASE64_ALPHABET[triple as usize & 0x3f] as char);
+        } else {
+            encoded.push('=');
+        }
+    }
+
+    encoded
+}
@@ -1,20 +1,464 @@
 use crate::{
-    client::ModelClient,
+    binaries,
+    client::{Message, ModelClient},
+    clipboard,
+    commands::plan,
     config::{Config, SystemPromptStyle},
+    exec::{self, Capture, ShellRun, SyntaxCheck},
+    extract::{self, Alternative},
+    grounding,
+    history::{self, HistoryEntry},
+    impact::{self, Impact},
+    interaction,
+    lint::{self, Finding},
     loader,
+    placeholder::{self, Filled, PlaceholderKind},
+    preview::{self, PreviewReport},
+    privilege::{self, Privilege},
+    risk::{self, RiskLevel, RiskReport},
+    snapshot, ui,
 };
 use anyhow::{Context, Result};
+use clap::Args;
 use crossterm::{
     cursor, execute,
     style::{Attribute, Color},
     terminal,
 };
 use futures::StreamExt;
-use std::io::{Write, stdout};
+use std::io::{IsTerminal, Write, stdout};
+use std::path::PathBuf;
+use std::process::ExitCode;
 use std::time::{Duration, Instant};
 use termimad::{Alignment, ListItemsIndentationMode, MadSkin};
 
-pub async fn run(question: &str) -> Result<()> {
+pub(crate) const CODE_COLOR: &str = "\x1b[90m";
+const WARNING_COLOR: &str = "\x1b[33m";
+pub(crate) const RESET: &str = "\x1b[0m";
+const MAX_MISSING_TOOL_FOLLOW_UPS: usize = 2;
+const MAX_LINT_FOLLOW_UPS: usize = 2;
+pub(crate) const STDERR_TAIL_BYTES: usize = 4000;
+/// Stdout sent back with --summarize: the start for headers and context, and
+/// more of the end, where totals and the latest entries usually are.
+const SUMMARY_HEAD_BYTES: usize = 6000;
+const SUMMARY_TAIL_BYTES: usize = 10000;
+/// Stdout kept with --timeout, so a command killed for taking too long can
+/// still be reported with what it printed last.
+const STDOUT_TAIL_BYTES: usize = 4000;
+
+#[derive(Debug, Default, Clone, PartialEq, Args)]
+pub struct ChatOptions {
+    /// Run the generated command in your shell after confirming it
+    #[arg(long, conflicts_with = "command_only")]
+    pub run: bool,
+
+    /// Print only the extracted command, for scripts and shell widgets
+    #[arg(long)]
+    pub command_only: bool,
+
+    /// Copy the generated command to the clipboard (OSC 52)
+    #[arg(long, conflicts_with = "command_only")]
+    pub copy: bool,
+
+    /// Ask the provider for an alternative when a suggested tool is not installed
+    #[arg(long)]
+    pub replace_missing: bool,
+
+    /// Run or copy the command even if it fails the shell's syntax check
+    #[arg(long, conflicts_with = "command_only")]
+    pub force: bool,
+
+    /// With --run, send failures back to the provider for a revised command, up to N times
+    #[arg(long, value_name = "N", default_value_t = 0, requires = "run")]
+    pub retry: u32,
+
+    /// Offer N alternative commands and pick one interactively
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u8).range(2..=9))]
+    pub alternatives: Option<u8>,
+
+    /// Dry-run the command in a throwaway sandbox and list the files it would change (Linux)
+    #[arg(long, conflicts_with = "command_only")]
+    pub preview: bool,
+
+    /// With --run, send the command's output back to the provider to answer the question
+    #[arg(long, requires = "run")]
+    pub summarize: bool,
+
+    /// With --run, stop the command and everything it started after DURATION (30s, 5m, 1h)
+    #[arg(long, value_name = "DURATION", value_parser = exec::parse_duration, requires = "run")]
+    pub timeout: Option<Duration>,
+
+    /// Send shell quoting findings back to the provider for a corrected command
+    #[arg(long, conflicts_with_all = ["command_only", "alternatives"])]
+    pub strict: bool,
+
+    /// Ask for a multi-step plan and walk through it one command at a time
+    #[arg(
+        long,
+        conflicts_with_all = ["command_only", "alternatives", "run", "copy", "preview", "strict"]
+    )]
+    pub plan: bool,
+}
+
+pub async fn run(question: &str, options: &ChatOptions) -> Result<ExitCode> {
+    let (config, client) = configured_client()?;
+    // `sudo -n` may be logged, so it is only tried when the command will run.
+    let privilege = privilege::current(options.run).await;
+    let system_prompt = match options.alternatives {
+        Some(count) => build_alternatives_prompt(&config.default_model, count),
+        None => build_system_prompt(
+            &config.default_model,
+            config.system_prompt_style,
+            &grounding::ground(question),
+            privilege,
+        ),
+    };
+    respond(&config, &client, question, &system_prompt, options).await
+}
+
+/// Asks for a command, then checks, copies, or runs it as `options` request.
+pub(crate) async fn respond(
+    config: &Config,
+    client: &ModelClient,
+    question: &str,
+    system_prompt: &str,
+    options: &ChatOptions,
+) -> Result<ExitCode> {
+    respond_from(config, client, question, system_prompt, options, None).await
+}
+
+/// Like [`respond`], but acts on `local` first when it is given: a response
+/// worked out without the provider. Follow-ups still go to the provider.
+pub(crate) async fn respond_from(
+    config: &Config,
+    client: &ModelClient,
+    question: &str,
+    system_prompt: &str,
+    options: &ChatOptions,
+    local: Option<String>,
+) -> Result<ExitCode> {
+    if options.command_only {
+        return print_command_only(client, config, question, system_prompt, options, local).await;
+    }
+    if options.plan {
+        return plan::run(config, client, question, options).await;
+    }
+
+    let replace_missing = options.replace_missing || config.replace_missing_tools;
+    let mut renderer = ResponseRenderer::new();
+    let mut conversation = vec![Message::user(question)];
+    let mut retries_left = options.retry;
+    let mut local = local;
+
+    loop {
+        let mut response = match local.take() {
+            Some(response) => {
+                renderer
+                    .render(&response)
+                    .context("Failed to render response")?;
+                renderer.finish();
+                response
+            }
+            None => {
+                stream_conversation(client, config, &conversation, system_prompt, &mut renderer)
+                    .await?
+            }
+        };
+        let command = match options.alternatives {
+            Some(_) => match pick_alternative(&response)? {
+                Picked::Command(command) => Some(command),
+                Picked::Dismissed => return Ok(ExitCode::SUCCESS),
+                Picked::NotAList => primary_command(&response),
+            },
+            None => {
+                response = check_missing_tools(
+                    client,
+                    config,
+                    system_prompt,
+                    &mut conversation,
+                    response,
+                    replace_missing,
+                    &mut renderer,
+                )
+                .await?;
+                if options.strict {
+                    response = fix_lint_findings(
+                        client,
+                        config,
+                        system_prompt,
+                        &mut conversation,
+                        response,
+                        &mut renderer,
+                    )
+                    .await?;
+                }
+                primary_command(&response)
+            }
+        };
+
+        let Some(suggested) = command else {
+            if options.run {
+                anyhow::bail!("Could not find a command to run in the response");
+            }
+            return Ok(ExitCode::SUCCESS);
+        };
+        // Placeholders and affected files only matter once the command leaves
+        // the answer, so a plain question skips both.
+        let acts_on_command =
+            options.run || options.copy || config.copy_to_clipboard || options.preview;
+        let filled = if acts_on_command {
+            fill_placeholders(&suggested)?
+        } else {
+            Filled::plain(&suggested)
+        };
+        let command = filled.command();
+        let command = command.as_str();
+
+        let report = risk::assess(command);
+        renderer
+            .print_risk_badge(&report)
+            .context("Failed to write risk badge")?;
+
+        let parses = match exec::check_syntax(command).await {
+            SyntaxCheck::Invalid(message) => {
+                renderer
+                    .print_warning(&filled.mask(&format!("Syntax error: {message}")))
+                    .context("Failed to write syntax warning")?;
+                options.force
+            }
+            SyntaxCheck::Valid | SyntaxCheck::Unavailable => true,
+        };
+        renderer
+            .print_lint_notes(&lint_filled(&filled))
+            .context("Failed to write lint notes")?;
+        let impacts = if parses && acts_on_command {
+            Some(
+                show_impact(&filled)
+                    .await
+                    .context("Failed to write impact summary")?,
+            )
+        } else {
+            None
+        };
+
+        if options.copy || config.copy_to_clipboard {
+            if parses {
+                copy_command(&filled, report.level())?;
+            } else {
+                eprintln!(
+                    "Command not copied because it does not parse. Pass --force to copy it anyway."
+                );
+            }
+        }
+
+        if options.preview && parses {
+            show_preview(&filled).await?;
+        }
+
+        if !options.run {
+            return Ok(ExitCode::SUCCESS);
+        }
+        if !parses {
+            eprintln!(
+                "Command not executed because it does not parse. Pass --force to run it anyway."
+            );
+            return Ok(ExitCode::FAILURE);
+        }
+
+        let capture = run_capture(options, retries_left > 0);
+        let Some((command, run)) = run_extracted_command(
+            config, question, &suggested, &filled, impacts, options, capture,
+        )
+        .await?
+        else {
+            return Ok(ExitCode::SUCCESS);
+        };
+        if run.status.success() || retries_left == 0 {
+            if let Some(limit) = run.timed_out {
+                renderer
+                    .print_warning(&format!(
+                        "Stopped the command after {}",
+                        loader::format_elapsed(limit)
+                    ))
+                    .context("Failed to write timeout notice")?;
+            }
+            if options.summarize {
+                summarize_output(client, config, question, &command, &run, &mut renderer).await?;
+            }
+            return Ok(run.exit_code());
+        }
+
+        retries_left -= 1;
+        renderer
+            .print_warning(&format!(
+                "Command failed with {}; asking for a revised command",
+                describe_status(&run)
+            ))
+            .context("Failed to write retry notice")?;
+        let follow_up = describe_failure(&command, &run);
+        conversation.push(Message::assistant(response));
+        conversation.push(Message::user(follow_up));
+    }
+}
+
+/// Output is only captured when it will be sent back to the provider.
+/// Anything else, including a plain `--timeout`, keeps the terminal, so
+/// colors, pagers and interactive programs behave as usual.
+fn run_capture(options: &ChatOptions, retrying: bool) -> Capture {
+    let stdout = if options.summarize {
+        Some((SUMMARY_HEAD_BYTES, SUMMARY_TAIL_BYTES))
+    } else {
+        // A command cut short by the timeout fails with no stderr to go on.
+        (retrying && options.timeout.is_some()).then_some((0, STDOUT_TAIL_BYTES))
+    };
+    let keep_stderr = retrying || options.summarize;
+    Capture {
+        stdout,
+        stderr_tail: keep_stderr.then_some(STDERR_TAIL_BYTES),
+    }
+}
+
+fn primary_command(response: &str) -> Option<String> {
+    extract::extract(response)
+        .primary_command()
+        .map(str::to_string)
+}
+
+enum Picked {
+    Command(String),
+    Dismissed,
+    /// The response did not hold several alternatives to choose from.
+    NotAList,
+}
+
+/// Shows the alternatives in the response as a list to pick from. Missing
+/// tools are flagged next to the command rather than asked about again.
+fn pick_alternative(response: &str) -> Result<Picked> {
+    let alternatives = extract::extract_alternatives(response);
+    if alternatives.len() < 2 {
+        return Ok(Picked::NotAList);
+    }
+
+    let labels: Vec<String> = alternatives.iter().map(alternative_label).collect();
+    println!();
+    match interaction::select_alternative(&labels)? {
+        Some(index) => Ok(Picked::Command(alternatives[index].command.clone())),
+        None => {
+            eprintln!("No command selected.");
+            Ok(Picked::Dismissed)
+        }
+    }
+}
+
+fn alternative_label(alternative: &Alternative) -> String {
+    let mut lines = alternative.command.lines();
+    let mut label = lines.next().unwrap_or_default().to_string();
+    if lines.next().is_some() {
+        label.push_str(" …");
+    }
+    if !alternative.tradeoff.is_empty() {
+        label.push_str(&format!("  — {}", alternative.tradeoff));
+    }
+
+    let missing = binaries::missing_programs(&alternative.command);
+    if !missing.is_empty() {
+        label.push_str(&format!(" ({} not installed)", describe_missing(&missing)));
+    }
+    label
+}
+
+/// Warns when the suggested command needs tools this machine lacks and, if
+/// `replace` is set, asks for alternatives. Returns the response to act on.
+async fn check_missing_tools(
+    client: &ModelClient,
+    config: &Config,
+    system_prompt: &str,
+    conversation: &mut Vec<Message>,
+    mut response: String,
+    replace: bool,
+    renderer: &mut ResponseRenderer,
+) -> Result<String> {
+    for follow_ups in 0.. {
+        let Some(command) = extract::extract(&response)
+            .primary_command()
+            .map(str::to_string)
+        else {
+            break;
+        };
+        let missing = binaries::missing_programs(&command);
+        if missing.is_empty() {
+            break;
+        }
+
+        renderer
+            .print_warning(&format!(
+                "{} not installed here",
+                describe_missing(&missing)
+            ))
+            .context("Failed to write missing tool warning")?;
+        if !replace || follow_ups == MAX_MISSING_TOOL_FOLLOW_UPS {
+            break;
+        }
+
+        conversation.push(Message::assistant(response));
+        conversation.push(Message::user(format!(
+            "{} not installed on this machine. Use an alternative that only relies on installed tools.",
+            describe_missing(&missing)
+        )));
+        response =
+            stream_conversation(client, config, conversation, system_prompt, renderer).await?;
+    }
+
+    Ok(response)
+}
+
+/// Sends the quoting findings on the suggested command back to the provider
+/// until it comes back clean or the follow-ups run out. Returns the response
+/// to act on.
+async fn fix_lint_findings(
+    client: &ModelClient,
+    config: &Config,
+    system_prompt: &str,
+    conversation: &mut Vec<Message>,
+    mut response: String,
+    renderer: &mut ResponseRenderer,
+) -> Result<String> {
+    for _ in 0..MAX_LINT_FOLLOW_UPS {
+        let Some(command) = primary_command(&response) else {
+            break;
+        };
+        let findings = lint::lint(&command);
+        if findings.is_empty() {
+            break;
+        }
+
+        renderer
+            .print_lint_notes(&findings)
+            .context("Failed to write lint notes")?;
+        renderer
+            .print_warning("Asking for a corrected command")
+            .context("Failed to write lint notice")?;
+        conversation.push(Message::assistant(response));
+        conversation.push(Message::user(describe_findings(&command, &findings)));
+        response =
+            stream_conversation(client, config, conversation, system_prompt, renderer).await?;
+    }
+
+    Ok(response)
+}
+
+fn describe_findings(command: &str, findings: &[Finding]) -> String {
+    let listed: Vec<String> = findings
+        .iter()
+        .map(|finding| format!("- {}", finding.message))
+        .collect();
+    format!(
+        "A shell linter found these problems in:\n\n```\n{command}\n```\n\n{}\n\nGive the corrected command.",
+        listed.join("\n")
+    )
+}
+
+pub(crate) fn configured_client() -> Result<(Config, ModelClient)> {
     let config = Config::load().context("Failed to load configuration")?;
 
     if !config.is_configured() {
@@ -23,12 +467,32 @@ pub async fn run(question: &str) -> Result<()> {
 
     let api_base = config.api_base();
     let client = ModelClient::new(config.provider, config.api_key.clone(), api_base);
+    Ok((config, client))
+}
+
+pub(crate) async fn stream_response(
+    client: &ModelClient,
+    config: &Config,
+    question: &str,
+    system_prompt: &str,
+    renderer: &mut ResponseRenderer,
+) -> Result<String> {
+    let conversation = [Message::user(question)];
+    stream_conversation(client, config, &conversation, system_prompt, renderer).await
+}
 
+/// Streams one provider response through the loader, header, and live
+/// markdown renderer, returning the full text once the stream ends.
+pub(crate) async fn stream_conversation(
+    client: &ModelClient,
+    config: &Config,
+    conversation: &[Message],
+    system_prompt: &str,
+    renderer: &mut ResponseRenderer,
+) -> Result<String> {
     let mut loader_handle = Some(loader::ShimmerLoader::new("tt is working").spawn());
     let start_time = Instant::now();
     let mut header_printed = false;
-    let mut renderer = ResponseRenderer::new();
-    let system_prompt = build_system_prompt(&config.default_model, config.system_prompt_style);
     let show_header = config.should_show_header();
     let header_model_holder = if config.should_show_model_in_header() {
         Some(config.default_model.clone())
@@ -38,7 +502,7 @@ pub async fn run(question: &str) -> Result<()> {
     let header_model = header_model_holder.as_deref();
 
     let mut stream = client
-        .ask_stream(question, &config.default_model, &system_prompt)
+        .chat_stream(conversation, &config.default_model, system_prompt)
         .await
         .context("Failed to get response from the provider")?;
 
@@ -78,9 +542,469 @@ pub async fn run(question: &str) -> Result<()> {
         println!("(No response received from the provider)");
     }
 
+    Ok(accumulated_text)
+}
+
+/// "`fd` is", "`fd` and `rg` are", "`a`, `b` and `c` are".
+fn describe_missing(missing: &[String]) -> String {
+    let quoted: Vec<String> = missing.iter().map(|name| format!("`{name}`")).collect();
+    match quoted.as_slice() {
+        [] => String::new(),
+        [single] => format!("{single} is"),
+        [init @ .., last] => format!("{} and {last} are", init.join(", ")),
+    }
+}
+
+pub(crate) fn describe_status(run: &ShellRun) -> String {
+    if let Some(limit) = run.timed_out {
+        return format!("a timeout after {}", loader::format_elapsed(limit));
+    }
+    match run.status.code() {
+        Some(code) => format!("exit code {code}"),
+        None => run.status.to_string(),
+    }
+}
+
+/// Sends what the command printed back with the original question, and
+/// renders the answer under the output.
+async fn summarize_output(
+    client: &ModelClient,
+    config: &Config,
+    question: &str,
+    command: &str,
+    run: &ShellRun,
+    renderer: &mut ResponseRenderer,
+) -> Result<()> {
+    let system_prompt = build_summarize_prompt(&config.default_model);
+    stream_response(
+        client,
+        config,
+        &summary_question(question, command, run),
+        &system_prompt,
+        renderer,
+    )
+    .await?;
+    Ok(())
+}
+
+fn summary_question(question: &str, command: &str, run: &ShellRun) -> String {
+    let status = if run.status.success() && run.timed_out.is_none() {
+        "It succeeded".to_string()
+    } else {
+        format!("It failed with {}", describe_status(run))
+    };
+    let mut message =
+        format!("My question was: {question}\n\nI ran:\n\n```\n{command}\n```\n\n{status}.");
+
+    let stdout = run.stdout.trim_end();
+    if stdout.is_empty() {
+        message.push_str("\n\nIt printed nothing to stdout.");
+    } else {
+        message.push_str(&format!("\n\nIts output:\n\n```\n{stdout}\n```"));
+    }
+    let stderr = run.stderr_tail.trim();
+    if !stderr.is_empty() {
+        message.push_str(&format!(
+            "\n\nThe end of its error output:\n\n```\n{stderr}\n```"
+        ));
+    }
+    message.push_str("\n\nAnswer my question from this output.");
+    message
+}
+
+fn describe_failure(command: &str, run: &ShellRun) -> String {
+    let mut message = failure_report(command, run);
+    message.push_str("\n\nFind the cause and propose a revised command.");
+    message
+}
+
+/// What ran and how it failed, with the end of its error output.
+pub(crate) fn failure_report(command: &str, run: &ShellRun) -> String {
+    let mut message = format!(
+        "I ran:\n\n```\n{command}\n```\n\nIt failed with {}.",
+        describe_status(run)
+    );
+    let stdout = run.stdout.trim_end();
+    if !stdout.is_empty() {
+        message.push_str(&format!("\n\nThe end of its output:\n\n```\n{stdout}\n```"));
+    }
+    let stderr = run.stderr_tail.trim();
+    if !stderr.is_empty() {
+        message.push_str(&format!(
+            "\n\nThe end of its error output:\n\n```\n{stderr}\n```"
+        ));
+    }
+    message
+}
+
+/// Prompts for each placeholder the model left in the command and substitutes
+/// the answers, quoted for where they land. Without a terminal to ask on, the
+/// command is returned as is.
+pub(crate) fn fill_placeholders(command: &str) -> Result<Filled> {
+    let placeholders = placeholder::find(command);
+    if placeholders.is_empty() || !std::io::stdin().is_terminal() {
+        return Ok(Filled::plain(command));
+    }
+
+    let dir = std::env::current_dir().unwrap_or_default();
+    println!();
+    let mut values = Vec::new();
+    for found in &placeholders {
+        let suggestions = match found.kind {
+            PlaceholderKind::Branch => placeholder::local_branches(&dir),
+            PlaceholderKind::Path => placeholder::matching_files(&dir, &found.token),
+            PlaceholderKind::Secret | PlaceholderKind::Text => Vec::new(),
+        };
+        let value = interaction::prompt_placeholder(found, &suggestions)?;
+        values.push((found.clone(), value));
+    }
+
+    // Secret values stay out of everything shown, so this prints them as
+    // the placeholders the model wrote.
+    let filled = Filled::new(command, &values);
+    if filled.shown != command {
+        println!("{CODE_COLOR}{}{RESET}", filled.shown);
+    }
+    Ok(filled)
+}
+
+/// Lint findings on the filled command, with secret values masked.
+pub(crate) fn lint_filled(filled: &Filled) -> Vec<Finding> {
+    lint::lint(&filled.command())
+        .into_iter()
+        .map(|finding| Finding {
+            message: filled.mask(&finding.message),
+            ..finding
+        })
+        .collect()
+}
+
+/// Lists what each `rm`, `mv`, `chmod`, `sed -i` or destructive `find` would
+/// touch, so nothing destructive reaches the confirmation prompt blind. The
+/// impacts come back so the snapshot taken before running can reuse them.
+pub(crate) async fn show_impact(filled: &Filled) -> Result<Vec<Impact>> {
+    let mut loader_handle = Some(loader::ShimmerLoader::new("Checking affected files").spawn());
+    let impacts = impact::assess(&filled.command()).await;
+    stop_loader(&mut loader_handle).await;
+
+    let mut out = stdout();
+    for impact in &impacts {
+        writeln!(out)?;
+        let description = filled.mask(&describe_impact(impact));
+        writeln!(out, "{WARNING_COLOR}! {description}{RESET}")?;
+        for diff in &impact.diffs {
+            writeln!(out)?;
+            for line in filled.mask(diff).lines() {
+                let color = match line.chars().next() {
+                    Some('+') if !line.starts_with("+++") => "\x1b[32m",
+                    Some('-') if !line.starts_with("---") => "\x1b[31m",
+                    _ => CODE_COLOR,
+                };
+                writeln!(out, "  {color}{line}{RESET}")?;
+            }
+        }
+    }
+    out.flush()?;
+    Ok(impacts)
+}
+
+fn describe_impact(impact: &Impact) -> String {
+    const SHOWN: usize = 10;
+    if impact.files.is_empty() {
+        return format!("{} will not affect any existing files", impact.action);
+    }
+
+    let count = group_thousands(impact.files.len());
+    let count = if impact.truncated {
+        format!("more than {count} files")
+    } else if impact.files.len() == 1 {
+        "1 file".to_string()
+    } else {
+        format!("{count} files")
+    };
+    let names: Vec<String> = impact
+        .files
+        .iter()
+        .take(SHOWN)
+        .map(|path| path.display().to_string())
+        .collect();
+    let listed = if impact.files.len() > SHOWN {
+        format!("first {SHOWN}: {}", names.join(", "))
+    } else {
+        names.join(", ")
+    };
+    format!("{} will affect {count} ({listed})", impact.action)
+}
+
+fn group_thousands(value: usize) -> String {
+    let digits = value.to_string();
+    let mut grouped = String::new();
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+/// Runs the command in the preview sandbox and prints what it would change.
+/// A preview that cannot run is reported but never blocks the real command.
+/// The preview really runs the command, with writes and the network cut off,
+/// so it asks the same confirmations as a real run first.
+async fn show_preview(filled: &Filled) -> Result<()> {
+    let command = filled.command();
+    let confirmed = (risk::assess(&command).level() < RiskLevel::High
+        || interaction::confirm_high_risk(&filled.shown, "preview")?)
+        && confirm_escalation(&filled.shown, "preview").await?;
+    if !confirmed {
+        eprintln!("Preview skipped.");
+        return Ok(());
+    }
+
+    let mut loader_handle = Some(loader::ShimmerLoader::new("Previewing in a sandbox").spawn());
+    let result = preview::preview(&command).await;
+    stop_loader(&mut loader_handle).await;
+
+    match result {
+        Ok(report) => ui::print_info_card("Sandbox preview", preview_rows(&report)),
+        Err(err) => eprintln!("Preview unavailable: {err:#}"),
+    }
+    Ok(())
+}
+
+fn preview_rows(report: &PreviewReport) -> Vec<(String, String)> {
+    let mut rows = if report.is_empty() {
+        vec![("Files".to_string(), "no changes".to_string())]
+    } else {
+        vec![
+            ("Created".to_string(), describe_paths(&report.created)),
+            ("Modified".to_string(), describe_paths(&report.modified)),
+            ("Deleted".to_string(), describe_paths(&report.deleted)),
+        ]
+    };
+    if let Some(code) = report.exit_code {
+        rows.push(("Exit code".to_string(), code.to_string()));
+    }
+    rows
+}
+
+fn describe_paths(paths: &[PathBuf]) -> String {
+    const SHOWN: usize = 4;
+    if paths.is_empty() {
+        return "none".to_string();
+    }
+
+    let mut names: Vec<String> = paths
+        .iter()
+        .take(SHOWN)
+        .map(|path| path.display().to_string())
+        .collect();
+    if paths.len() > SHOWN {
+        names.push(format!("and {} more", paths.len() - SHOWN));
+    }
+    names.join(", ")
+}
+
+fn copy_command(filled: &Filled, risk: RiskLevel) -> Result<()> {
+    if risk == RiskLevel::High {
+        println!();
+        if !interaction::confirm_high_risk(&filled.shown, "copy")? {
+            eprintln!("Command not copied.");
+            return Ok(());
+        }
+    }
+
+    clipboard::copy(&filled.command())?;
+    print_response_footer("Copied to clipboard");
     Ok(())
 }
 
+/// Buffers the whole response and prints just the command, so shell widgets
+/// can capture stdout. Risk findings go to stderr where the user still sees them.
+async fn print_command_only(
+    client: &ModelClient,
+    config: &Config,
+    question: &str,
+    system_prompt: &str,
+    options: &ChatOptions,
+    local: Option<String>,
+) -> Result<ExitCode> {
+    let response = match local {
+        Some(response) => response,
+        None => {
+            let mut stream = client
+                .ask_stream(question, &config.default_model, system_prompt)
+                .await
+                .context("Failed to get response from the provider")?;
+
+            let mut response = String::new();
+            while let Some(result) = stream.next().await {
+                response.push_str(&result?);
+            }
+            response
+        }
+    };
+
+    let command = match options.alternatives {
+        Some(_) => match pick_alternative(&response)? {
+            Picked::Command(command) => Some(command),
+            Picked::Dismissed => return Ok(ExitCode::FAILURE),
+            Picked::NotAList => primary_command(&response),
+        },
+        None => primary_command(&response),
+    };
+    let Some(command) = command.as_deref() else {
+        eprintln!("tt: no command found in the response");
+        return Ok(ExitCode::FAILURE);
+    };
+
+    let missing = binaries::missing_programs(command);
+    if !missing.is_empty() {
+        eprintln!("tt: warning: {} not installed", describe_missing(&missing));
+    }
+    if let SyntaxCheck::Invalid(message) = exec::check_syntax(command).await {
+        eprintln!("tt: warning: syntax error: {message}");
+    }
+    for finding in lint::lint(command) {
+        eprintln!("tt: note: {}", finding.message);
+    }
+
+    let report = risk::assess(command);
+    if report.level() > RiskLevel::Low {
+        let reasons: Vec<&str> = report
+            .findings
+            .iter()
+            .map(|finding| finding.reason.as_str())
+            .collect();
+        eprintln!("tt: {}: {}", report.level().label(), reasons.join("; "));
+    }
+
+    println!("{command}");
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Lets the user edit and confirm the command, runs what they settled on, and
+/// records both versions in the history. `None` means nothing ran; otherwise
+/// the command comes back as shown, without its secrets.
+async fn run_extracted_command(
+    config: &Config,
+    question: &str,
+    suggested: &str,
+    filled: &Filled,
+    impacts: Option<Vec<Impact>>,
+    options: &ChatOptions,
+    capture: Capture,
+) -> Result<Option<(String, ShellRun)>> {
+    println!();
+    let Some(edited) = interaction::edit_command(&filled.shown)? else {
+        eprintln!("Command not executed.");
+        return Ok(None);
+    };
+    let unedited = filled.command();
+    let filled = filled.edited(edited);
+    let impacts = impacts.filter(|_| filled.command() == unedited);
+    let command = filled.shown.clone();
+
+    if command != suggested && !options.force {
+        if let SyntaxCheck::Invalid(message) = exec::check_syntax(&filled.command()).await {
+            let message = filled.mask(&message);
+            anyhow::bail!(
+                "The edited command does not parse ({message}). Pass --force to run it anyway."
+            );
+        }
+    }
+    if risk::assess(&filled.command()).level() == RiskLevel::High
+        && !interaction::confirm_high_risk(&command, "run")?
+    {
+        eprintln!("Command not executed.");
+        return Ok(None);
+    }
+    if !confirm_escalation(&command, "run").await? {
+        eprintln!("Command not executed.");
+        return Ok(None);
+    }
+
+    let run = execute(
+        config,
+        question,
+        suggested,
+        &filled,
+        impacts,
+        capture,
+        options.timeout,
+    )
+    .await?;
+    Ok(Some((command, run)))
+}
+
+/// Points out each part of `command` that runs through `sudo` or `doas` and
+/// asks separately before acting on it. Commands that do not escalate pass.
+pub(crate) async fn confirm_escalation(command: &str, action: &str) -> Result<bool> {
+    let escalations = privilege::escalations(command);
+    let Some(first) = escalations.first() else {
+        return Ok(true);
+    };
+
+    for escalation in &escalations {
+        eprintln!(
+            "{WARNING_COLOR}! `{}` runs `{}` with elevated privileges{RESET}",
+            escalation.tool, escalation.command
+        );
+    }
+    if privilege::current(true)
+        .await
+        .is_some_and(|privilege| !privilege.can_escalate())
+    {
+        eprintln!(
+            "{WARNING_COLOR}! You do not appear to have `{}` rights here, so it will likely be refused{RESET}",
+            first.tool
+        );
+    }
+    interaction::confirm_escalation(&first.tool, action)
+}
+
+/// Runs `filled` and records it in the history, along with the state
+/// captured just before it ran. The history gets it as shown, so secret
+/// values are never written to disk. `impacts` are those already shown for
+/// this exact command; `None` assesses it afresh.
+pub(crate) async fn execute(
+    config: &Config,
+    question: &str,
+    suggested: &str,
+    filled: &Filled,
+    impacts: Option<Vec<Impact>>,
+    capture: Capture,
+    timeout: Option<Duration>,
+) -> Result<ShellRun> {
+    let command = filled.command();
+    let command = command.as_str();
+    let impacts = match impacts {
+        Some(impacts) => impacts,
+        None => impact::assess(command).await,
+    };
+    let snapshot = snapshot::capture(&impacts).await;
+    let run = if capture == Capture::default() && timeout.is_none() {
+        ShellRun {
+            status: exec::run_in_shell(command).await?,
+            stderr_tail: String::new(),
+            stdout: String::new(),
+            timed_out: None,
+        }
+    } else {
+        exec::run_in_shell_with_timeout(command, capture, timeout).await?
+    };
+
+    let mut entry = HistoryEntry::new(question, &config.default_model, suggested, &filled.shown);
+    entry.exit_code = run.status.code();
+    entry.snapshot = Some(snapshot);
+    if let Err(err) = history::append(&entry) {
+        eprintln!("Warning: could not record history ({err})");
+    }
+
+    Ok(run)
+}
+
 async fn stop_loader(loader_handle: &mut Option<loader::LoaderHandle>) {
     if let Some(mut handle) = loader_handle.take() {
         handle.stop().await;
@@ -93,11 +1017,26 @@ async fn stop_loader(loader_handle: &mut Option<loader::LoaderHandle>) {
     }
 }
 
-fn build_system_prompt(model_name: &str, style: SystemPromptStyle) -> String {
+fn environment_block(model_name: &str) -> String {
     let os_name = current_os_display_name();
     let shell_name = current_shell_display_name();
-    let environment =
-        format!("Environment:\n- OS: {os_name}\n- Shell: {shell_name}\n- Model: {model_name}");
+    format!("Environment:\n- OS: {os_name}\n- Shell: {shell_name}\n- Model: {model_name}")
+}
+
+fn build_system_prompt(
+    model_name: &str,
+    style: SystemPromptStyle,
+    mentions: &[grounding::Mention],
+    privilege: Option<&Privilege>,
+) -> String {
+    let privilege = privilege
+        .map(|privilege| format!("\n- Privileges: {privilege}"))
+        .unwrap_or_default();
+    let environment = format!(
+        "{}{privilege}{}",
+        environment_block(model_name),
+        grounding::prompt_block(mentions)
+    );
 
     match style {
         SystemPromptStyle::Command => format!(
@@ -112,6 +1051,55 @@ fn build_system_prompt(model_name: &str, style: SystemPromptStyle) -> String {
     }
 }
 
+pub(crate) fn build_explain_prompt(model_name: &str) -> String {
+    let environment = environment_block(model_name);
+    format!(
+        "You are `tt-cli` in explain mode. The user pastes a shell command together with a breakdown of its pipeline stages, flags, and redirections. Explain what it does.\n\n{environment}\n\nFormat:\n- One top-level bullet per stage, starting with the stage in backticks.\n- Nested bullets for each flag, argument, and redirection in that stage, each with a short explanation.\n- End with one sentence on what the whole command achieves and anything risky about it.\n- Explain the command as written; do not rewrite it unless it is broken."
+    )
+}
+
+pub(crate) fn build_alternatives_prompt(model_name: &str, count: u8) -> String {
+    let environment = environment_block(model_name);
+    format!(
+        "You are `tt-cli` in alternatives mode. The request may be ambiguous, so offer {count} distinct ways to do it and let the user choose.\n\n{environment}\n\nFormat:\n- A numbered list with exactly {count} items and nothing before or after it.\n- Each item is one command in backticks, then ` — ` and a one-line trade-off (speed, safety, portability, or what it assumes).\n- Make the approaches genuinely different, not the same command with cosmetic changes.\n- No placeholders. Quote paths and variables safely."
+    )
+}
+
+pub(crate) fn build_fix_prompt(model_name: &str) -> String {
+    let environment = environment_block(model_name);
+    format!(
+        "You are `tt-cli` in fix mode. The user ran a shell command that failed and shares the command, its exit status, and any error output. Work out what went wrong and give the corrected command.\n\n{environment}\n\nRules:\n- Start with one short sentence naming the mistake (a typo, a wrong flag, a missing argument, and so on).\n- Then give exactly one corrected command in backticks.\n- Keep the user's intent and change as little as possible.\n- If the command cannot be fixed from the information given, say what is missing instead of guessing."
+    )
+}
+
+pub(crate) fn build_plan_prompt(model_name: &str) -> String {
+    let environment = environment_block(model_name);
+    format!(
+        "You are `tt-cli` in plan mode. The task needs several commands, so break it into ordered steps that the user runs one at a time.\n\n{environment}\n\nFormat:\n- A numbered list of steps and nothing before or after it.\n- Each item is one command in backticks, then ` — ` and a one-line purpose.\n- Every step runs in a fresh shell from the same working directory, so `cd`, `export` and `source` do not carry over. Use paths, or join dependent commands with `&&` inside one step.\n- Keep steps small enough to check one at a time. No placeholders. Quote paths and variables safely."
+    )
+}
+
+pub(crate) fn build_undo_prompt(model_name: &str) -> String {
+    let environment = environment_block(model_name);
+    format!(
+        "You are `tt-cli` in undo mode. The user shares a shell command that `tt` ran and a snapshot of the state just before it ran. Give the command that reverses its effects.\n\n{environment}\n\nRules:\n- Start with one short sentence saying what the command changed.\n- Then give exactly one command in backticks that restores the earlier state, using the snapshot (the previous git HEAD, the original file names, and so on).\n- If the effects cannot be reversed (deleted files with no copy, overwritten contents, data sent elsewhere), say plainly that it cannot be undone and why, and give no command.\n- Never suggest a command that destroys more data than the original did."
+    )
+}
+
+pub(crate) fn build_summarize_prompt(model_name: &str) -> String {
+    let environment = environment_block(model_name);
+    format!(
+        "You are `tt-cli` in summarize mode. The user asked a question, ran a command to answer it, and shares the command's output. Answer the question they asked from that output.\n\n{environment}\n\nRules:\n- Lead with the direct answer in one or two sentences, quoting the figures or names that support it.\n- Add a short bullet list only when several items matter (the largest directories, the failing pods, and so on).\n- Long output arrives with its middle elided; say so if the answer could be in the missing part.\n- Do not suggest another command unless the output cannot answer the question."
+    )
+}
+
+pub(crate) fn build_translate_prompt(model_name: &str, from: &str, to: &str) -> String {
+    let environment = environment_block(model_name);
+    format!(
+        "You are `tt-cli` in translate mode. The user shares a {from} command or short script, often with a list of the constructs it uses. Rewrite it in {to} with the same behaviour.\n\n{environment}\n\nRules:\n- Translate every listed construct: variable syntax, `export` and assignments, loops, conditionals, functions, command and process substitution, arithmetic, and heredocs.\n- Use idiomatic {to} (for fish: `set -x`, `(cmd)`, `psub`; for PowerShell: `$env:`, `foreach`, cmdlets) rather than calling the original shell.\n- Give exactly one translation in a fenced block, preceded by at most one sentence on anything that has no direct equivalent.\n- Keep paths, flags, and quoting safe. No placeholders."
+    )
+}
+
 fn current_os_display_name() -> String {
     match std::env::consts::OS {
         "macos" => "macOS",
@@ -124,12 +1112,7 @@ fn current_os_display_name() -> String {
 }
 
 fn current_shell_display_name() -> String {
-    std::env::var("SHELL")
-        .or_else(|_| std::env::var("COMSPEC"))
-        .map(|value| value.trim().to_string())
-        .ok()
-        .filter(|value| !value.is_empty())
-        .unwrap_or_else(|| "unknown".to_string())
+    exec::user_shell().unwrap_or_else(|| "unknown".to_string())
 }
 
 fn codex_skin() -> MadSkin {
@@ -171,13 +1154,13 @@ fn codex_skin() -> MadSkin {
     skin
 }
 
-struct ResponseRenderer {
+pub(crate) struct ResponseRenderer {
     skin: MadSkin,
     rendered_lines: Vec<String>,
 }
 
 impl ResponseRenderer {
-    fn new() -> Self {
+    pub(crate) fn new() -> Self {
         Self {
             skin: codex_skin(),
             rendered_lines: Vec::new(),
@@ -231,6 +1214,63 @@ impl ResponseRenderer {
     fn has_output(&self) -> bool {
         !self.rendered_lines.is_empty()
     }
+
+    pub(crate) fn print_warning(&self, message: &str) -> Result<()> {
+        let mut out = stdout();
+        writeln!(out)?;
+        writeln!(out, "{WARNING_COLOR}! {message}{RESET}")?;
+        out.flush()?;
+        Ok(())
+    }
+
+    pub(crate) fn print_note(&self, message: &str) -> Result<()> {
+        let mut out = stdout();
+        writeln!(out)?;
+        writeln!(out, "{CODE_COLOR}note: {message}{RESET}")?;
+        out.flush()?;
+        Ok(())
+    }
+
+    /// Dimmed, since findings are advice rather than reasons not to run.
+    pub(crate) fn print_lint_notes(&self, findings: &[Finding]) -> Result<()> {
+        if findings.is_empty() {
+            return Ok(());
+        }
+
+        let mut out = stdout();
+        writeln!(out)?;
+        for finding in findings {
+            writeln!(out, "{CODE_COLOR}note: {}{RESET}", finding.message)?;
+        }
+        out.flush()?;
+        Ok(())
+    }
+
+    pub(crate) fn print_risk_badge(&self, report: &RiskReport) -> Result<()> {
+        let badge_color = match report.level() {
+            RiskLevel::Low => return Ok(()),
+            RiskLevel::Medium => "\x1b[1;30;43m",
+            RiskLevel::High => "\x1b[1;97;41m",
+        };
+
+        let reasons: Vec<&str> = report
+            .findings
+            .iter()
+            .filter(|finding| finding.level > RiskLevel::Low)
+            .map(|finding| finding.reason.as_str())
+            .collect();
+
+        let mut out = stdout();
+        writeln!(out)?;
+        writeln!(
+            out,
+            "{badge_color} {} {RESET} {CODE_COLOR}{}{RESET}",
+            report.level().label(),
+            reasons.join("; ")
+        )?;
+        out.flush()?;
+        Ok(())
+    }
 }
 
 fn count_common_prefix(a: &[String], b: &[String]) -> usize {
@@ -241,9 +1281,24 @@ fn count_common_prefix(a: &[String], b: &[String]) -> usize {
 }
 
 fn print_response_header(duration: Duration, model: Option<&str>) -> Result<()> {
-    let (width, _) = terminal::size().unwrap_or((100, 0));
     let label = format!("Worked for {}", loader::format_elapsed(duration));
-    let model_label = model.map(|m| format!(" {m} "));
+    let line = rule_line(&label, model);
+
+    println!();
+    println!("{CODE_COLOR}{line}{RESET}");
+    println!();
+
+    Ok(())
+}
+
+fn print_response_footer(label: &str) {
+    println!();
+    println!("{CODE_COLOR}{}{RESET}", rule_line(label, None));
+}
+
+fn rule_line(label: &str, suffix: Option<&str>) -> String {
+    let (width, _) = terminal::size().unwrap_or((100, 0));
+    let model_label = suffix.map(|m| format!(" {m} "));
 
     let desired_width = width.max(20) as usize;
     let left_part = format!("─ {label} ");
@@ -272,11 +1327,5 @@ fn print_response_header(duration: Duration, model: Option<&str>) -> Result<()>
         }
     }
 
-    const CODE_COLOR: &str = "\x1b[90m";
-    const RESET: &str = "\x1b[0m";
-    println!();
-    println!("{CODE_COLOR}{line}{RESET}");
-    println!();
-
-    Ok(())
+    line
 }
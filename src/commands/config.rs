@@ -44,6 +44,18 @@ pub fn show() -> Result<()> {
             "Prompt Style".to_string(),
             config.system_prompt_style.display_name().to_string(),
         ),
+        (
+            "Copy Command".to_string(),
+            humanize_bool(config.copy_to_clipboard),
+        ),
+        (
+            "Replace Missing".to_string(),
+            humanize_bool(config.replace_missing_tools),
+        ),
+        (
+            "Offline Typo Fix".to_string(),
+            humanize_bool(config.offline_typo_fix),
+        ),
         ("Config File".to_string(), config_path.display().to_string()),
     ];
 
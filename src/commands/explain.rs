@@ -0,0 +1,149 @@
+use crate::commands::chat::{self, ResponseRenderer};
+use crate::risk;
+use crate::shell::{self, Connector, Redirect, SimpleCommand};
+use anyhow::{Context, Result};
+
+pub async fn run(command: &str) -> Result<()> {
+    let command = command.trim();
+    if command.is_empty() {
+        anyhow::bail!("Please provide a command to explain.");
+    }
+
+    let (config, client) = chat::configured_client()?;
+    let system_prompt = chat::build_explain_prompt(&config.default_model);
+    let question = format!(
+        "Explain this command:\n\n```\n{command}\n```\n\n{}",
+        describe_segments(command)
+    );
+
+    let mut renderer = ResponseRenderer::new();
+    chat::stream_response(&client, &config, &question, &system_prompt, &mut renderer).await?;
+    renderer
+        .print_risk_badge(&risk::assess(command))
+        .context("Failed to write risk badge")?;
+
+    Ok(())
+}
+
+/// Splits the command locally so the model explains every piece instead of
+/// summarising the whole line.
+fn describe_segments(command: &str) -> String {
+    let list = shell::parse_partial(command);
+    let stages = list.stages();
+    if stages.is_empty() {
+        return String::new();
+    }
+
+    let mut lines = vec!["Segments:".to_string()];
+    for (index, stage) in stages.iter().enumerate() {
+        lines.push(format!(
+            "{}. `{}` ({})",
+            index + 1,
+            stage_source(stage.command),
+            describe_connector(stage.connector)
+        ));
+
+        let command = stage.command;
+        let (flags, arguments): (Vec<&str>, Vec<&str>) = command
+            .words
+            .iter()
+            .skip(1)
+            .map(|word| word.raw.as_str())
+            .partition(|raw| raw.starts_with('-') && raw.len() > 1);
+
+        let assignments: Vec<&str> = command
+            .assignments
+            .iter()
+            .map(|word| word.raw.as_str())
+            .collect();
+        let redirects: Vec<String> = command.redirects.iter().map(format_redirect).collect();
+        let substitutions: Vec<&str> = command
+            .substitutions()
+            .iter()
+            .map(|substitution| substitution.source.as_str())
+            .collect();
+
+        push_detail(&mut lines, "environment", &assignments);
+        push_detail(&mut lines, "flags", &flags);
+        push_detail(&mut lines, "arguments", &arguments);
+        push_detail(&mut lines, "redirections", &redirects);
+        push_detail(&mut lines, "substitutions", &substitutions);
+    }
+
+    lines.join("\n")
+}
+
+fn stage_source(stage: &SimpleCommand) -> String {
+    let mut parts: Vec<String> = stage
+        .assignments
+        .iter()
+        .chain(&stage.words)
+        .map(|word| word.raw.clone())
+        .collect();
+    parts.extend(stage.redirects.iter().map(format_redirect));
+    parts.join(" ")
+}
+
+fn format_redirect(redirect: &Redirect) -> String {
+    let fd = redirect.fd.map(|fd| fd.to_string()).unwrap_or_default();
+    format!("{fd}{}{}", redirect.op.as_str(), redirect.target.raw)
+}
+
+fn describe_connector(connector: Connector) -> &'static str {
+    match connector {
+        Connector::Start => "runs first",
+        Connector::Pipe => "reads the previous stage's output through a pipe",
+        Connector::And => "runs only if the previous command succeeded",
+        Connector::Or => "runs only if the previous command failed",
+        Connector::Sequence => "runs after the previous command",
+        Connector::Background => "runs while the previous command continues in the background",
+    }
+}
+
+fn push_detail<S: AsRef<str>>(lines: &mut Vec<String>, label: &str, items: &[S]) {
+    if items.is_empty() {
+        return;
+    }
+    let rendered: Vec<String> = items
+        .iter()
+        .map(|item| format!("`{}`", item.as_ref()))
+        .collect();
+    lines.push(format!("   - {label}: {}", rendered.join(", ")));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segments_cover_stages_flags_and_redirections() {
+        let description =
+            describe_segments("LC_ALL=C grep -rn 'TODO' src 2>/dev/null | sort -u > todo.txt");
+        assert_eq!(
+            description,
+            "Segments:\n\
+             1. `LC_ALL=C grep -rn 'TODO' src 2>/dev/null` (runs first)\n\
+             \x20  - environment: `LC_ALL=C`\n\
+             \x20  - flags: `-rn`\n\
+             \x20  - arguments: `'TODO'`, `src`\n\
+             \x20  - redirections: `2>/dev/null`\n\
+             2. `sort -u >todo.txt` (reads the previous stage's output through a pipe)\n\
+             \x20  - flags: `-u`\n\
+             \x20  - redirections: `>todo.txt`"
+        );
+    }
+
+    #[test]
+    fn conditional_chains_are_described() {
+        let description = describe_segments("make && ./run || echo failed");
+        assert!(description.contains("2. `./run` (runs only if the previous command succeeded)"));
+        assert!(
+            description.contains("3. `echo failed` (runs only if the previous command failed)")
+        );
+    }
+
+    #[test]
+    fn empty_input_has_no_segments() {
+        assert!(describe_segments("   ").is_empty());
+    }
+}
@@ -0,0 +1,175 @@
+use crate::commands::chat::{self, ChatOptions};
+use crate::typo::{self, Correction};
+use anyhow::{Context, Result};
+use std::io::{IsTerminal, Read};
+use std::process::ExitCode;
+
+/// Exported by the `tt init` shell integration after every command.
+const LAST_COMMAND_VAR: &str = "TT_LAST_COMMAND";
+const LAST_STATUS_VAR: &str = "TT_LAST_STATUS";
+const MAX_STDERR_CHARS: usize = 4000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FailedCommand {
+    command: String,
+    status: Option<i32>,
+    stderr: Option<String>,
+}
+
+pub async fn run(
+    command: Option<String>,
+    status: Option<i32>,
+    stderr: Option<String>,
+    options: &ChatOptions,
+) -> Result<ExitCode> {
+    let failed = resolve(command, status, stderr)?;
+    let (config, client) = chat::configured_client()?;
+    let system_prompt = match options.alternatives {
+        Some(count) => chat::build_alternatives_prompt(&config.default_model, count),
+        None => chat::build_fix_prompt(&config.default_model),
+    };
+    // Alternatives and plans need the provider's judgement, not a spelling fix.
+    let local = if config.offline_typo_fix && options.alternatives.is_none() && !options.plan {
+        typo::correct(&failed.command).map(|correction| describe_correction(&correction))
+    } else {
+        None
+    };
+    chat::respond_from(
+        &config,
+        &client,
+        &fix_question(&failed),
+        &system_prompt,
+        options,
+        local,
+    )
+    .await
+}
+
+/// Written like a provider's fix response, so it renders and extracts the
+/// same way.
+fn describe_correction(correction: &Correction) -> String {
+    let fixes: Vec<String> = correction
+        .fixes
+        .iter()
+        .map(|fix| format!("`{}` for `{}`", fix.typo, fix.replacement))
+        .collect();
+    format!(
+        "Looks like a typo: {} (fixed locally).\n\n```\n{}\n```",
+        fixes.join(" and "),
+        correction.command
+    )
+}
+
+/// Fills in whatever was not passed as a flag from the shell integration's
+/// variables, and reads error output from stdin when it is piped.
+fn resolve(
+    command: Option<String>,
+    status: Option<i32>,
+    stderr: Option<String>,
+) -> Result<FailedCommand> {
+    let command = command
+        .or_else(|| std::env::var(LAST_COMMAND_VAR).ok())
+        .map(|command| command.trim().to_string())
+        .filter(|command| !command.is_empty())
+        .context(
+            "No previous command found. Pass --command, or load the shell integration with `tt init`.",
+        )?;
+
+    let status = status.or_else(|| std::env::var(LAST_STATUS_VAR).ok()?.trim().parse().ok());
+
+    let stderr = match stderr {
+        Some(stderr) => Some(stderr),
+        None if !std::io::stdin().is_terminal() => {
+            let mut piped = String::new();
+            std::io::stdin()
+                .read_to_string(&mut piped)
+                .context("Failed to read error output from stdin")?;
+            Some(piped)
+        }
+        None => None,
+    }
+    .filter(|stderr| !stderr.trim().is_empty());
+
+    Ok(FailedCommand {
+        command,
+        status,
+        stderr,
+    })
+}
+
+fn fix_question(failed: &FailedCommand) -> String {
+    let mut question = format!("This command failed:\n\n```\n{}\n```", failed.command);
+    if let Some(status) = failed.status {
+        question.push_str(&format!("\n\nExit status: {status}"));
+    }
+    if let Some(stderr) = &failed.stderr {
+        question.push_str(&format!(
+            "\n\nError output:\n\n```\n{}\n```",
+            tail(stderr.trim_end(), MAX_STDERR_CHARS)
+        ));
+    }
+    question
+}
+
+/// The last `max_chars` characters of `text`; the end of error output is
+/// usually where the actual error is.
+fn tail(text: &str, max_chars: usize) -> &str {
+    let skip = text.chars().count().saturating_sub(max_chars);
+    match text.char_indices().nth(skip) {
+        Some((index, _)) => &text[index..],
+        None => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn question_includes_status_and_error_output() {
+        let failed = FailedCommand {
+            command: "git comit -m wip".to_string(),
+            status: Some(1),
+            stderr: Some("git: 'comit' is not a git command.\n".to_string()),
+        };
+        assert_eq!(
+            fix_question(&failed),
+            "This command failed:\n\n```\ngit comit -m wip\n```\n\nExit status: 1\n\n\
+             Error output:\n\n```\ngit: 'comit' is not a git command.\n```"
+        );
+    }
+
+    #[test]
+    fn question_omits_unknown_details() {
+        let failed = FailedCommand {
+            command: "tar -xz archive.tgz".to_string(),
+            status: None,
+            stderr: None,
+        };
+        assert_eq!(
+            fix_question(&failed),
+            "This command failed:\n\n```\ntar -xz archive.tgz\n```"
+        );
+    }
+
+    #[test]
+    fn corrections_read_like_a_fix_response() {
+        let correction = Correction {
+            command: "git status".to_string(),
+            fixes: vec![typo::Fix {
+                typo: "gti".to_string(),
+                replacement: "git".to_string(),
+            }],
+        };
+        assert_eq!(
+            describe_correction(&correction),
+            "Looks like a typo: `gti` for `git` (fixed locally).\n\n```\ngit status\n```"
+        );
+    }
+
+    #[test]
+    fn long_error_output_keeps_the_end() {
+        assert_eq!(tail("héllo wörld", 5), "wörld");
+        assert_eq!(tail("short", 10), "short");
+    }
+}
@@ -0,0 +1,105 @@
+use anyhow::Result;
+use clap::ValueEnum;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum InitShell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+const BASH_SNIPPET: &str = r#"# tt shell integration for bash. Add to ~/.bashrc:
+#   eval "$(tt init bash)"
+# Press Ctrl-G to turn the current line into a command, and run `tt fix`
+# after a failed command to get a corrected one.
+__tt_widget() {
+  [ -z "$READLINE_LINE" ] && return
+  local __tt_cmd
+  __tt_cmd="$(command tt --command-only -- "$READLINE_LINE" </dev/tty)" || return
+  if [ -n "$__tt_cmd" ]; then
+    READLINE_LINE="$__tt_cmd"
+    READLINE_POINT=${#READLINE_LINE}
+  fi
+}
+bind -x '"\C-g": __tt_widget'
+__tt_record_last() {
+  local __tt_status=$? __tt_last
+  read -r _ __tt_last <<<"$(HISTTIMEFORMAT= builtin history 1)"
+  case "$__tt_last" in
+    ''|"tt fix"*|"command tt fix"*) ;;
+    *) export TT_LAST_COMMAND="$__tt_last" TT_LAST_STATUS="$__tt_status" ;;
+  esac
+  return $__tt_status
+}
+case ";$PROMPT_COMMAND;" in
+  *";__tt_record_last;"*) ;;
+  *) PROMPT_COMMAND="__tt_record_last${PROMPT_COMMAND:+;$PROMPT_COMMAND}" ;;
+esac
+"#;
+
+const ZSH_SNIPPET: &str = r#"# tt shell integration for zsh. Add to ~/.zshrc:
+#   eval "$(tt init zsh)"
+# Press Ctrl-G to turn the current line into a command, and run `tt fix`
+# after a failed command to get a corrected one.
+__tt_widget() {
+  [[ -z "$BUFFER" ]] && return
+  local __tt_cmd
+  zle -I
+  if __tt_cmd="$(command tt --command-only -- "$BUFFER" </dev/tty)" && [[ -n "$__tt_cmd" ]]; then
+    BUFFER="$__tt_cmd"
+    CURSOR=${#BUFFER}
+  fi
+  zle reset-prompt
+}
+zle -N __tt_widget
+bindkey '^G' __tt_widget
+__tt_preexec() {
+  __tt_last_command=$1
+}
+__tt_precmd() {
+  local __tt_status=$?
+  case "$__tt_last_command" in
+    ''|"tt fix"*|"command tt fix"*) ;;
+    *) export TT_LAST_COMMAND="$__tt_last_command" TT_LAST_STATUS=$__tt_status ;;
+  esac
+  __tt_last_command=
+}
+autoload -Uz add-zsh-hook
+add-zsh-hook preexec __tt_preexec
+add-zsh-hook precmd __tt_precmd
+"#;
+
+const FISH_SNIPPET: &str = r#"# tt shell integration for fish. Add to ~/.config/fish/config.fish:
+#   tt init fish | source
+# Press Ctrl-G to turn the current line into a command, and run `tt fix`
+# after a failed command to get a corrected one.
+function __tt_widget
+    set -l __tt_question (commandline)
+    test -z "$__tt_question"; and return
+    set -l __tt_cmd (command tt --command-only -- "$__tt_question" </dev/tty | string collect)
+    and test -n "$__tt_cmd"
+    and commandline -r -- $__tt_cmd
+    commandline -f repaint
+end
+bind \cg __tt_widget
+function __tt_record_last --on-event fish_postexec
+    set -l __tt_status $status
+    test -z "$argv[1]"; and return
+    string match -q -r '^(command )?tt fix\b' -- $argv[1]; and return
+    set -gx TT_LAST_COMMAND $argv[1]
+    set -gx TT_LAST_STATUS $__tt_status
+end
+"#;
+
+pub fn snippet(shell: InitShell) -> &'static str {
+    match shell {
+        InitShell::Bash => BASH_SNIPPET,
+        InitShell::Zsh => ZSH_SNIPPET,
+        InitShell::Fish => FISH_SNIPPET,
+    }
+}
+
+pub fn run(shell: InitShell) -> Result<()> {
+    print!("{}", snippet(shell));
+    Ok(())
+}
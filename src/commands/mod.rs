@@ -1,4 +1,10 @@
 pub mod chat;
 pub mod config;
+pub mod explain;
+pub mod fix;
+pub mod init;
 pub mod model;
+pub mod plan;
 pub mod setup;
+pub mod translate;
+pub mod undo;
@@ -0,0 +1,303 @@
+use crate::commands::chat::{self, CODE_COLOR, ChatOptions, RESET, ResponseRenderer};
+use crate::{
+    client::{Message, ModelClient},
+    config::Config,
+    exec::{self, Capture, ShellRun, SyntaxCheck},
+    extract::{self, PlanStep},
+    interaction::{self, StepAction},
+    lint,
+    placeholder::Filled,
+    risk::{self, RiskLevel},
+    ui,
+};
+use anyhow::{Context, Result};
+use std::process::ExitCode;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Outcome {
+    Ran(Option<i32>),
+    Skipped,
+}
+
+/// One line of the record of what the plan actually did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TranscriptEntry {
+    command: String,
+    outcome: Outcome,
+}
+
+enum StepResult {
+    Ran(String, ShellRun),
+    Skipped,
+    Aborted,
+}
+
+/// Asks for a numbered plan and walks it step by step. A failed step stops
+/// the walk and offers to plan the rest again from there.
+pub(crate) async fn run(
+    config: &Config,
+    client: &ModelClient,
+    question: &str,
+    options: &ChatOptions,
+) -> Result<ExitCode> {
+    let system_prompt = chat::build_plan_prompt(&config.default_model);
+    let mut renderer = ResponseRenderer::new();
+    let mut conversation = vec![Message::user(question)];
+    let mut transcript: Vec<TranscriptEntry> = Vec::new();
+
+    loop {
+        let response =
+            chat::stream_conversation(client, config, &conversation, &system_prompt, &mut renderer)
+                .await?;
+        let steps = extract::extract_plan(&response);
+        if steps.is_empty() {
+            anyhow::bail!("Could not find any steps in the response");
+        }
+
+        let mut failure = None;
+        for (index, step) in steps.iter().enumerate() {
+            print_step(index, steps.len(), step);
+            match run_step(config, question, &step.command, options, &renderer).await? {
+                StepResult::Ran(command, run) => {
+                    transcript.push(TranscriptEntry {
+                        command: command.clone(),
+                        outcome: Outcome::Ran(run.status.code()),
+                    });
+                    if !run.status.success() {
+                        failure = Some((command, run));
+                        break;
+                    }
+                }
+                StepResult::Skipped => transcript.push(TranscriptEntry {
+                    command: step.command.clone(),
+                    outcome: Outcome::Skipped,
+                }),
+                StepResult::Aborted => {
+                    ui::print_info_card("Plan aborted", transcript_rows(&transcript));
+                    return Ok(ExitCode::FAILURE);
+                }
+            }
+        }
+
+        let Some((command, run)) = failure else {
+            ui::print_info_card("Plan finished", transcript_rows(&transcript));
+            return Ok(ExitCode::SUCCESS);
+        };
+
+        renderer
+            .print_warning(&format!("Step failed with {}", chat::describe_status(&run)))
+            .context("Failed to write step failure")?;
+        println!();
+        if !interaction::prompt_toggle("Re-plan the remaining steps from here?", true)? {
+            ui::print_info_card("Plan stopped", transcript_rows(&transcript));
+            return Ok(run.exit_code());
+        }
+
+        conversation.push(Message::assistant(response));
+        conversation.push(Message::user(replan_request(&transcript, &command, &run)));
+    }
+}
+
+fn print_step(index: usize, total: usize, step: &PlanStep) {
+    println!();
+    println!("\x1b[1mStep {}/{total}\x1b[0m {}", index + 1, step.purpose);
+    for line in step.command.lines() {
+        println!("  {CODE_COLOR}{line}{RESET}");
+    }
+}
+
+/// Shows the same checks as a single command, then lets the user run, skip,
+/// edit or abort. Clearing an edit goes back to the choice. Placeholders are
+/// only filled once the user picks Run, so skipped steps ask for nothing.
+async fn run_step(
+    config: &Config,
+    question: &str,
+    suggested: &str,
+    options: &ChatOptions,
+    renderer: &ResponseRenderer,
+) -> Result<StepResult> {
+    renderer
+        .print_risk_badge(&risk::assess(suggested))
+        .context("Failed to write risk badge")?;
+    if let SyntaxCheck::Invalid(message) = exec::check_syntax(suggested).await {
+        renderer
+            .print_warning(&format!("Syntax error: {message}"))
+            .context("Failed to write syntax warning")?;
+    }
+    renderer
+        .print_lint_notes(&lint::lint(suggested))
+        .context("Failed to write lint notes")?;
+    let impacts = chat::show_impact(&Filled::plain(suggested))
+        .await
+        .context("Failed to write impact summary")?;
+
+    // Kept across choices so a declined confirmation does not ask again.
+    let mut filled: Option<Filled> = None;
+    let mut shown = suggested.to_string();
+    loop {
+        println!();
+        match interaction::select_step_action()? {
+            StepAction::Run => {}
+            StepAction::Skip => return Ok(StepResult::Skipped),
+            StepAction::Abort => return Ok(StepResult::Aborted),
+            StepAction::Edit => match interaction::edit_command(&shown)? {
+                Some(edited) => {
+                    filled = filled.map(|filled| filled.edited(edited.clone()));
+                    shown = edited;
+                }
+                None => continue,
+            },
+        }
+
+        let filled = match &filled {
+            Some(filled) => filled,
+            None => filled.insert(chat::fill_placeholders(&shown)?),
+        };
+        shown = filled.shown.clone();
+        let command = filled.command();
+        if !options.force {
+            if let SyntaxCheck::Invalid(message) = exec::check_syntax(&command).await {
+                let message = filled.mask(&message);
+                eprintln!(
+                    "The step does not parse ({message}). Edit or skip it, or pass --force to run it anyway."
+                );
+                continue;
+            }
+        }
+        if risk::assess(&command).level() == RiskLevel::High
+            && !interaction::confirm_high_risk(&filled.shown, "run")?
+        {
+            continue;
+        }
+        if !chat::confirm_escalation(&filled.shown, "run").await? {
+            continue;
+        }
+
+        let capture = Capture {
+            stdout: None,
+            stderr_tail: Some(chat::STDERR_TAIL_BYTES),
+        };
+        // Reuse the impacts only while they describe the command that runs.
+        let impacts = (command == suggested).then(|| impacts.clone());
+        let run =
+            chat::execute(config, question, suggested, filled, impacts, capture, None).await?;
+        let command = filled.shown.clone();
+        return Ok(StepResult::Ran(command, run));
+    }
+}
+
+fn transcript_rows(transcript: &[TranscriptEntry]) -> Vec<(String, String)> {
+    if transcript.is_empty() {
+        return vec![("Steps".to_string(), "nothing ran".to_string())];
+    }
+
+    transcript
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            let first_line = entry.command.lines().next().unwrap_or_default();
+            let command = if entry.command.contains('\n') {
+                format!("{first_line} …")
+            } else {
+                first_line.to_string()
+            };
+            (
+                (index + 1).to_string(),
+                format!("{:<9} {command}", describe_outcome(&entry.outcome)),
+            )
+        })
+        .collect()
+}
+
+fn describe_outcome(outcome: &Outcome) -> String {
+    match outcome {
+        Outcome::Ran(Some(0)) => "ok".to_string(),
+        Outcome::Ran(Some(code)) => format!("exit {code}"),
+        Outcome::Ran(None) => "killed".to_string(),
+        Outcome::Skipped => "skipped".to_string(),
+    }
+}
+
+fn replan_request(transcript: &[TranscriptEntry], command: &str, run: &ShellRun) -> String {
+    let history: Vec<String> = transcript
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            format!(
+                "{}. `{}` ({})",
+                index + 1,
+                entry.command,
+                describe_outcome(&entry.outcome)
+            )
+        })
+        .collect();
+
+    format!(
+        "{}\n\nWhat has happened so far:\n{}\n\nPlan the remaining steps from here. Do not repeat steps that already succeeded.",
+        chat::failure_report(command, run),
+        history.join("\n")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(command: &str, outcome: Outcome) -> TranscriptEntry {
+        TranscriptEntry {
+            command: command.to_string(),
+            outcome,
+        }
+    }
+
+    #[test]
+    fn transcript_rows_show_outcomes() {
+        let transcript = vec![
+            entry("python3 -m venv .venv", Outcome::Ran(Some(0))),
+            entry("brew update", Outcome::Skipped),
+            entry("pytest\n-q", Outcome::Ran(Some(1))),
+        ];
+        let values: Vec<String> = transcript_rows(&transcript)
+            .into_iter()
+            .map(|(_, value)| value)
+            .collect();
+        assert_eq!(
+            values,
+            vec![
+                "ok        python3 -m venv .venv",
+                "skipped   brew update",
+                "exit 1    pytest …",
+            ]
+        );
+    }
+
+    #[test]
+    fn replan_request_lists_what_ran() {
+        let transcript = vec![
+            entry("make deps", Outcome::Ran(Some(0))),
+            entry("make test", Outcome::Ran(Some(2))),
+        ];
+        let run = ShellRun {
+            status: exit_status(2),
+            stderr_tail: "no rule to make target 'test'\n".to_string(),
+            stdout: String::new(),
+            timed_out: None,
+        };
+        let request = replan_request(&transcript, "make test", &run);
+        assert!(request.contains("It failed with exit code 2."));
+        assert!(request.contains("1. `make deps` (ok)\n2. `make test` (exit 2)"));
+        assert!(request.ends_with("Do not repeat steps that already succeeded."));
+    }
+
+    #[cfg(unix)]
+    fn exit_status(code: i32) -> std::process::ExitStatus {
+        use std::os::unix::process::ExitStatusExt;
+        std::process::ExitStatus::from_raw(code << 8)
+    }
+
+    #[cfg(windows)]
+    fn exit_status(code: i32) -> std::process::ExitStatus {
+        use std::os::windows::process::ExitStatusExt;
+        std::process::ExitStatus::from_raw(code as u32)
+    }
+}
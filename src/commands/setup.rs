@@ -35,6 +35,18 @@ pub async fn run() -> Result<()> {
         false
     };
     let prompt_style = interaction::select_prompt_style(config.system_prompt_style)?;
+    let copy_to_clipboard = interaction::prompt_toggle(
+        "Copy generated commands to the clipboard (OSC 52)",
+        config.copy_to_clipboard,
+    )?;
+    let replace_missing_tools = interaction::prompt_toggle(
+        "Ask for an alternative when a suggested tool is not installed",
+        config.replace_missing_tools,
+    )?;
+    let offline_typo_fix = interaction::prompt_toggle(
+        "Fix typos in failed commands locally before asking the provider",
+        config.offline_typo_fix,
+    )?;
 
     config.provider = provider;
     config.api_key = api_key;
@@ -47,6 +59,9 @@ pub async fn run() -> Result<()> {
     config.show_header = show_header;
     config.show_model_in_header = show_model_in_header;
     config.system_prompt_style = prompt_style;
+    config.copy_to_clipboard = copy_to_clipboard;
+    config.replace_missing_tools = replace_missing_tools;
+    config.offline_typo_fix = offline_typo_fix;
 
     config.save().context("Failed to save configuration")?;
 
@@ -0,0 +1,313 @@
+use crate::binaries;
+use crate::commands::chat::{self, ResponseRenderer};
+use crate::exec::{self, ShellKind, SyntaxCheck};
+use crate::extract;
+use crate::shell::{self, Command, Compound, List, RedirectOp, Word, WordPart};
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Dialect {
+    Sh,
+    Bash,
+    Zsh,
+    Fish,
+    #[value(alias = "pwsh")]
+    Powershell,
+}
+
+impl Dialect {
+    /// Executables to syntax-check with, in order of preference.
+    fn programs(self) -> &'static [&'static str] {
+        match self {
+            Dialect::Sh => &["sh"],
+            Dialect::Bash => &["bash"],
+            Dialect::Zsh => &["zsh"],
+            Dialect::Fish => &["fish"],
+            Dialect::Powershell => &["pwsh", "powershell"],
+        }
+    }
+
+    fn kind(self) -> ShellKind {
+        match self {
+            Dialect::Sh => ShellKind::Sh,
+            Dialect::Bash => ShellKind::Bash,
+            Dialect::Zsh => ShellKind::Zsh,
+            Dialect::Fish => ShellKind::Fish,
+            Dialect::Powershell => ShellKind::PowerShell,
+        }
+    }
+
+    /// Whether `tt::shell` can parse source written in this dialect.
+    fn is_posix(self) -> bool {
+        matches!(self, Dialect::Sh | Dialect::Bash | Dialect::Zsh)
+    }
+}
+
+impl fmt::Display for Dialect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Dialect::Sh => "POSIX sh",
+            Dialect::Bash => "bash",
+            Dialect::Zsh => "zsh",
+            Dialect::Fish => "fish",
+            Dialect::Powershell => "PowerShell",
+        })
+    }
+}
+
+pub async fn run(command: &str, from: Dialect, to: Dialect) -> Result<()> {
+    let command = command.trim();
+    if command.is_empty() {
+        anyhow::bail!("Please provide a command to translate.");
+    }
+    if from == to {
+        anyhow::bail!("The command is already {to}; pick a different --to or --from.");
+    }
+
+    let (config, client) = chat::configured_client()?;
+    let system_prompt =
+        chat::build_translate_prompt(&config.default_model, &from.to_string(), &to.to_string());
+    let mut question = format!("Translate this {from} command to {to}:\n\n```\n{command}\n```");
+    if from.is_posix() {
+        let constructs = describe_constructs(command);
+        if !constructs.is_empty() {
+            question.push_str(&format!("\n\n{constructs}"));
+        }
+    }
+
+    let mut renderer = ResponseRenderer::new();
+    let response =
+        chat::stream_response(&client, &config, &question, &system_prompt, &mut renderer).await?;
+    let Some(translated) = extract::extract(&response)
+        .primary_command()
+        .map(str::to_string)
+    else {
+        return Ok(());
+    };
+
+    check_translation(&renderer, &translated, to)
+        .await
+        .context("Failed to write syntax check result")
+}
+
+/// Parses the translation with the target shell when it is installed, since
+/// the user's own shell cannot tell whether it is valid.
+async fn check_translation(renderer: &ResponseRenderer, command: &str, to: Dialect) -> Result<()> {
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let Some(program) = to
+        .programs()
+        .iter()
+        .find(|program| binaries::is_on_path(program, &path))
+    else {
+        return renderer.print_note(&format!(
+            "{to} is not installed here, so the translation was not syntax-checked"
+        ));
+    };
+
+    match exec::check_syntax_with(program, to.kind(), command).await {
+        SyntaxCheck::Valid => renderer.print_note(&format!("Parses with {program}")),
+        SyntaxCheck::Invalid(message) => {
+            renderer.print_warning(&format!("Syntax error in {to}: {message}"))
+        }
+        SyntaxCheck::Unavailable => renderer.print_note(&format!(
+            "{program} has no parse-only mode, so the translation was not syntax-checked"
+        )),
+    }
+}
+
+/// Lists the constructs the parser finds, so the model accounts for each one
+/// instead of translating only the obvious parts.
+fn describe_constructs(command: &str) -> String {
+    let list = shell::parse_partial(command);
+    let mut constructs = Constructs::default();
+    constructs.add_list(&list);
+
+    let mut lines = vec!["Constructs to translate:".to_string()];
+    push_detail(&mut lines, "variables", &constructs.variables);
+    push_detail(&mut lines, "exports", &constructs.exports);
+    push_detail(&mut lines, "assignments", &constructs.assignments);
+    push_detail(&mut lines, "loops", &constructs.loops);
+    push_detail(&mut lines, "conditionals", &constructs.conditionals);
+    push_detail(&mut lines, "functions", &constructs.functions);
+    push_detail(
+        &mut lines,
+        "command substitutions",
+        &constructs.substitutions,
+    );
+    push_detail(
+        &mut lines,
+        "process substitutions",
+        &constructs.process_substitutions,
+    );
+    push_detail(&mut lines, "arithmetic", &constructs.arithmetic);
+    push_detail(&mut lines, "heredocs", &constructs.heredocs);
+    if lines.len() == 1 {
+        return String::new();
+    }
+    lines.join("\n")
+}
+
+#[derive(Default)]
+struct Constructs {
+    variables: Vec<String>,
+    exports: Vec<String>,
+    assignments: Vec<String>,
+    loops: Vec<String>,
+    conditionals: Vec<String>,
+    functions: Vec<String>,
+    substitutions: Vec<String>,
+    process_substitutions: Vec<String>,
+    arithmetic: Vec<String>,
+    heredocs: Vec<String>,
+}
+
+impl Constructs {
+    fn add_list(&mut self, list: &List) {
+        for command in list.commands() {
+            self.add_command(command);
+            for word in command.words() {
+                self.add_parts(&word.parts);
+            }
+        }
+        for substitution in list.substitutions() {
+            self.add_list(&substitution.body);
+        }
+    }
+
+    fn add_command(&mut self, command: &Command) {
+        match command {
+            Command::Simple(simple) => {
+                let program = simple.program().unwrap_or_default();
+                let arguments = simple.words.iter().skip(1);
+                if matches!(program, "export" | "declare" | "typeset" | "local") {
+                    let target = if program == "export" {
+                        &mut self.exports
+                    } else {
+                        &mut self.assignments
+                    };
+                    for word in arguments.filter(|word| !word.raw.starts_with('-')) {
+                        push_unique(target, &word.raw);
+                    }
+                }
+                for assignment in &simple.assignments {
+                    // `NAME=value cmd` only sets the variable for `cmd`.
+                    let target = if simple.words.is_empty() {
+                        &mut self.assignments
+                    } else {
+                        &mut self.exports
+                    };
+                    push_unique(target, &assignment.raw);
+                }
+                for redirect in &simple.redirects {
+                    self.add_heredoc(redirect.op, &redirect.target);
+                }
+            }
+            Command::Compound(compound) => {
+                match &compound.kind {
+                    Compound::For { variable, .. } => {
+                        push_unique(&mut self.loops, &format!("for {variable}"))
+                    }
+                    Compound::Select { variable, .. } => {
+                        push_unique(&mut self.loops, &format!("select {variable}"))
+                    }
+                    Compound::ArithmeticFor { header, .. } => {
+                        push_unique(&mut self.loops, &format!("for (({header}))"))
+                    }
+                    Compound::While { .. } => push_unique(&mut self.loops, "while"),
+                    Compound::Until { .. } => push_unique(&mut self.loops, "until"),
+                    Compound::If { .. } => push_unique(&mut self.conditionals, "if"),
+                    Compound::Case { .. } => push_unique(&mut self.conditionals, "case"),
+                    Compound::Test(_) => push_unique(&mut self.conditionals, "[[ ]]"),
+                    Compound::Arithmetic(expression) => {
+                        push_unique(&mut self.arithmetic, &format!("(({expression}))"))
+                    }
+                    Compound::Subshell(_) | Compound::Group(_) => {}
+                }
+                for redirect in &compound.redirects {
+                    self.add_heredoc(redirect.op, &redirect.target);
+                }
+            }
+            Command::Function(function) => push_unique(&mut self.functions, &function.name),
+        }
+    }
+
+    fn add_parts(&mut self, parts: &[WordPart]) {
+        for part in parts {
+            match part {
+                WordPart::Parameter(written) => push_unique(&mut self.variables, written),
+                WordPart::DoubleQuoted(inner) => self.add_parts(inner),
+                WordPart::CommandSubstitution(_) | WordPart::Backticks(_) => {
+                    push_unique(&mut self.substitutions, &part.text())
+                }
+                WordPart::ProcessSubstitution(..) => {
+                    push_unique(&mut self.process_substitutions, &part.text())
+                }
+                WordPart::Arithmetic(_) => push_unique(&mut self.arithmetic, &part.text()),
+                WordPart::Literal(_) | WordPart::SingleQuoted(_) | WordPart::Escaped(_) => {}
+            }
+        }
+    }
+
+    fn add_heredoc(&mut self, op: RedirectOp, target: &Word) {
+        if matches!(op, RedirectOp::Heredoc | RedirectOp::HeredocStripTabs) {
+            push_unique(
+                &mut self.heredocs,
+                &format!("{}{}", op.as_str(), target.raw),
+            );
+        }
+    }
+}
+
+fn push_unique(items: &mut Vec<String>, item: &str) {
+    if !items.iter().any(|existing| existing == item) {
+        items.push(item.to_string());
+    }
+}
+
+fn push_detail(lines: &mut Vec<String>, label: &str, items: &[String]) {
+    if items.is_empty() {
+        return;
+    }
+    let rendered: Vec<String> = items.iter().map(|item| format!("`{item}`")).collect();
+    lines.push(format!("- {label}: {}", rendered.join(", ")));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constructs_cover_variables_loops_and_substitutions() {
+        let description = describe_constructs(
+            "export PATH=\"$HOME/bin:$PATH\"; for f in *.log; do diff <(sort \"$f\") \"$(basename \"$f\")\"; done",
+        );
+        assert_eq!(
+            description,
+            "Constructs to translate:\n\
+             - variables: `$HOME`, `$PATH`, `$f`\n\
+             - exports: `PATH=\"$HOME/bin:$PATH\"`\n\
+             - loops: `for f`\n\
+             - command substitutions: `$(basename \"$f\")`\n\
+             - process substitutions: `<(sort \"$f\")`"
+        );
+    }
+
+    #[test]
+    fn conditionals_assignments_and_heredocs_are_listed() {
+        let description = describe_constructs(
+            "count=$((n + 1)); if [[ -f a ]]; then cat <<EOF\nhi\nEOF\nfi; greet() { echo hi; }",
+        );
+        assert!(description.contains("- assignments: `count=$((n + 1))`"));
+        assert!(description.contains("- conditionals: `if`, `[[ ]]`"));
+        assert!(description.contains("- functions: `greet`"));
+        assert!(description.contains("- arithmetic: `$((n + 1))`"));
+        assert!(description.contains("- heredocs: `<<EOF`"));
+    }
+
+    #[test]
+    fn plain_commands_need_no_construct_list() {
+        assert!(describe_constructs("ls -la /tmp | wc -l").is_empty());
+    }
+}
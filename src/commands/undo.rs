@@ -0,0 +1,154 @@
+use crate::commands::chat::{self, ChatOptions};
+use crate::history::{self, HistoryEntry};
+use crate::snapshot::Snapshot;
+use anyhow::{Context, Result};
+use std::process::ExitCode;
+
+const SHOWN_FILES: usize = 50;
+
+pub async fn run(options: &ChatOptions) -> Result<ExitCode> {
+    let entry = history::load()?
+        .pop()
+        .context("No command to undo yet. Commands run with `tt --run` are recorded for this.")?;
+
+    if let Some(snapshot) = &entry.snapshot {
+        let here = std::env::current_dir().ok();
+        if here.as_deref() != Some(snapshot.cwd.as_path()) {
+            eprintln!(
+                "Note: `{}` ran in {}. Undo it from there if the inverse uses relative paths.",
+                entry.executed,
+                snapshot.cwd.display()
+            );
+        }
+    }
+
+    let (config, client) = chat::configured_client()?;
+    let system_prompt = chat::build_undo_prompt(&config.default_model);
+    chat::respond(
+        &config,
+        &client,
+        &undo_question(&entry),
+        &system_prompt,
+        options,
+    )
+    .await
+}
+
+fn undo_question(entry: &HistoryEntry) -> String {
+    let mut question = format!("Undo this command:\n\n```\n{}\n```", entry.executed);
+    if let Some(code) = entry.exit_code {
+        question.push_str(&format!("\n\nExit status: {code}"));
+    }
+    match &entry.snapshot {
+        Some(snapshot) => {
+            question.push_str("\n\nState captured just before it ran:\n");
+            question.push_str(&describe_snapshot(snapshot));
+        }
+        None => question.push_str("\n\nNo snapshot of the state before it ran is available."),
+    }
+    question
+}
+
+fn describe_snapshot(snapshot: &Snapshot) -> String {
+    let mut lines = vec![format!("- Working directory: {}", snapshot.cwd.display())];
+    if let Some(head) = &snapshot.git_head {
+        let branch = snapshot
+            .git_branch
+            .as_deref()
+            .map(|branch| format!(" on branch {branch}"))
+            .unwrap_or_else(|| " (detached)".to_string());
+        lines.push(format!("- Git HEAD: {head}{branch}"));
+        if snapshot.git_dirty {
+            lines.push("- The work tree had uncommitted changes".to_string());
+        }
+    }
+    for targets in &snapshot.targets {
+        let mut files: Vec<String> = targets
+            .files
+            .iter()
+            .take(SHOWN_FILES)
+            .map(|path| path.display().to_string())
+            .collect();
+        if targets.truncated {
+            files.push(format!("and over {} more", targets.total - files.len()));
+        } else if targets.total > files.len() {
+            files.push(format!("and {} more", targets.total - files.len()));
+        }
+        let listed = if files.is_empty() {
+            "no existing files".to_string()
+        } else {
+            files.join(", ")
+        };
+        lines.push(format!("- `{}` targets: {listed}", targets.action));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::Targets;
+    use std::path::PathBuf;
+
+    fn entry(snapshot: Option<Snapshot>) -> HistoryEntry {
+        let mut entry = HistoryEntry::new("q", "model", "mv a.txt b.txt", "mv a.txt b.txt");
+        entry.exit_code = Some(0);
+        entry.snapshot = snapshot;
+        entry
+    }
+
+    #[test]
+    fn question_lists_the_snapshot() {
+        let snapshot = Snapshot {
+            cwd: PathBuf::from("/work"),
+            git_head: Some("abc123".to_string()),
+            git_branch: Some("main".to_string()),
+            git_dirty: true,
+            targets: vec![Targets {
+                action: "mv".to_string(),
+                files: vec![PathBuf::from("a.txt")],
+                total: 1,
+                truncated: false,
+            }],
+        };
+        assert_eq!(
+            undo_question(&entry(Some(snapshot))),
+            "Undo this command:\n\n```\nmv a.txt b.txt\n```\n\nExit status: 0\n\n\
+             State captured just before it ran:\n- Working directory: /work\n\
+             - Git HEAD: abc123 on branch main\n- The work tree had uncommitted changes\n\
+             - `mv` targets: a.txt"
+        );
+    }
+
+    #[test]
+    fn question_says_when_there_is_no_snapshot() {
+        assert!(
+            undo_question(&entry(None))
+                .ends_with("No snapshot of the state before it ran is available.")
+        );
+    }
+
+    #[test]
+    fn long_target_lists_are_cut_short() {
+        let snapshot = Snapshot {
+            cwd: PathBuf::from("/work"),
+            targets: vec![Targets {
+                action: "rm".to_string(),
+                files: (0..60).map(|i| PathBuf::from(format!("f{i}"))).collect(),
+                total: 300,
+                truncated: false,
+            }],
+            ..Snapshot::default()
+        };
+        assert!(describe_snapshot(&snapshot).ends_with("f49, and 250 more"));
+
+        let snapshot = Snapshot {
+            targets: vec![Targets {
+                truncated: true,
+                ..snapshot.targets[0].clone()
+            }],
+            ..snapshot
+        };
+        assert!(describe_snapshot(&snapshot).ends_with("f49, and over 250 more"));
+    }
+}
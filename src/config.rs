@@ -78,6 +78,14 @@ pub struct Config {
     pub show_model_in_header: bool,
     #[serde(default)]
     pub system_prompt_style: SystemPromptStyle,
+    #[serde(default)]
+    pub copy_to_clipboard: bool,
+    #[serde(default)]
+    pub replace_missing_tools: bool,
+    /// Let `tt fix` correct misspelled program and subcommand names locally
+    /// before asking the provider.
+    #[serde(default)]
+    pub offline_typo_fix: bool,
 }
 
 fn default_model() -> String {
@@ -104,6 +112,9 @@ impl Default for Config {
             show_header: default_show_header(),
             show_model_in_header: default_show_model_in_header(),
             system_prompt_style: SystemPromptStyle::default(),
+            copy_to_clipboard: false,
+            replace_missing_tools: false,
+            offline_typo_fix: false,
         }
     }
 }
@@ -0,0 +1,601 @@
+use crate::loader::{LoaderHandle, ShimmerLoader};
+use anyhow::{Context, Result};
+use crossterm::{cursor, execute, terminal};
+use std::io::IsTerminal;
+use std::process::{ExitCode, ExitStatus, Stdio};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::process::Command;
+use tokio::sync::{Mutex, MutexGuard};
+
+const SYNTAX_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long a timed-out command gets to exit after SIGTERM before SIGKILL.
+const KILL_GRACE: Duration = Duration::from_secs(2);
+/// Silence after which the "Still running" loader appears.
+const IDLE_DELAY: Duration = Duration::from_secs(2);
+const IDLE_POLL: Duration = Duration::from_millis(250);
+/// What `timeout(1)` exits with when the limit runs out.
+pub const TIMEOUT_EXIT_CODE: u8 = 124;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShellKind {
+    Sh,
+    Bash,
+    Zsh,
+    Fish,
+    PowerShell,
+    Cmd,
+}
+
+impl ShellKind {
+    pub fn from_program(program: &str) -> Option<Self> {
+        let file_name = program.trim().rsplit(['/', '\\']).next()?;
+        let name = file_name.to_ascii_lowercase();
+        let name = name.strip_suffix(".exe").unwrap_or(&name);
+
+        match name {
+            "sh" | "dash" | "ash" | "ksh" => Some(ShellKind::Sh),
+            "bash" => Some(ShellKind::Bash),
+            "zsh" => Some(ShellKind::Zsh),
+            "fish" => Some(ShellKind::Fish),
+            "pwsh" | "powershell" => Some(ShellKind::PowerShell),
+            "cmd" => Some(ShellKind::Cmd),
+            _ => None,
+        }
+    }
+
+    fn command_flag(&self) -> &'static str {
+        match self {
+            ShellKind::PowerShell => "-Command",
+            ShellKind::Cmd => "/C",
+            _ => "-c",
+        }
+    }
+
+    /// The flag that makes the shell parse its input without running it.
+    pub fn parse_only_flag(&self) -> Option<&'static str> {
+        match self {
+            ShellKind::Sh | ShellKind::Bash | ShellKind::Zsh => Some("-n"),
+            ShellKind::Fish => Some("--no-execute"),
+            ShellKind::PowerShell | ShellKind::Cmd => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyntaxCheck {
+    Valid,
+    /// The shell rejected the command; holds the first line of its error.
+    Invalid(String),
+    /// The shell has no parse-only mode or could not be started.
+    Unavailable,
+}
+
+/// The shell the user is running, taken from `$SHELL` (or `%COMSPEC%` on Windows).
+pub fn user_shell() -> Option<String> {
+    std::env::var("SHELL")
+        .or_else(|_| std::env::var("COMSPEC"))
+        .map(|value| value.trim().to_string())
+        .ok()
+        .filter(|value| !value.is_empty())
+}
+
+pub(crate) fn resolve_shell() -> (String, ShellKind) {
+    if let Some(program) = user_shell() {
+        if let Some(kind) = ShellKind::from_program(&program) {
+            return (program, kind);
+        }
+    }
+
+    if cfg!(windows) {
+        ("cmd".to_string(), ShellKind::Cmd)
+    } else {
+        ("sh".to_string(), ShellKind::Sh)
+    }
+}
+
+/// The outcome of a command run through the user's shell.
+#[derive(Debug)]
+pub struct ShellRun {
+    pub status: ExitStatus,
+    /// The end of what the command wrote to stderr, when it was captured.
+    pub stderr_tail: String,
+    /// The start and end of what the command wrote to stdout, when it was
+    /// captured, with anything in between elided.
+    pub stdout: String,
+    /// The limit that ran out, when the command was killed for taking too long.
+    pub timed_out: Option<Duration>,
+}
+
+impl ShellRun {
+    /// Like [`exit_code`], but a timeout reports 124 as `timeout(1)` does.
+    pub fn exit_code(&self) -> ExitCode {
+        match self.timed_out {
+            Some(_) => ExitCode::from(TIMEOUT_EXIT_CODE),
+            None => exit_code(&self.status),
+        }
+    }
+}
+
+/// Which streams [`run_in_shell_with_timeout`] keeps, and how much of them.
+/// Captured streams still reach the terminal as they are written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Capture {
+    /// Bytes kept from the start and from the end of stdout.
+    pub stdout: Option<(usize, usize)>,
+    /// Bytes kept from the end of stderr.
+    pub stderr_tail: Option<usize>,
+}
+
+fn shell_command(command: &str) -> (String, Command) {
+    let (program, kind) = resolve_shell();
+    let mut shell = Command::new(&program);
+    shell
+        .arg(kind.command_flag())
+        .arg(command)
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::inherit());
+    (program, shell)
+}
+
+/// Runs `command` through the user's shell with the terminal's stdio attached,
+/// so output streams live and interactive programs keep working.
+pub async fn run_in_shell(command: &str) -> Result<ExitStatus> {
+    let (program, mut shell) = shell_command(command);
+
+    let mut child = shell
+        .stderr(Stdio::inherit())
+        .spawn()
+        .with_context(|| format!("Failed to start {program}"))?;
+
+    child
+        .wait()
+        .await
+        .with_context(|| format!("Failed to wait for {program}"))
+}
+
+/// Like [`run_in_shell`], but the streams named in `capture` pass through
+/// `tt`, which keeps part of them. Programs writing to a captured stream see
+/// a pipe rather than a terminal. Once `timeout` passes, the command's whole
+/// process group gets SIGTERM, then SIGKILL if it is still around after a
+/// grace period. The group is given the terminal while it runs, so prompts
+/// and Ctrl-C still reach it.
+pub async fn run_in_shell_with_timeout(
+    command: &str,
+    capture: Capture,
+    timeout: Option<Duration>,
+) -> Result<ShellRun> {
+    let (program, mut shell) = shell_command(command);
+    if capture.stdout.is_some() {
+        shell.stdout(Stdio::piped());
+    }
+    let stderr = if capture.stderr_tail.is_some() {
+        Stdio::piped()
+    } else {
+        Stdio::inherit()
+    };
+    #[cfg(unix)]
+    if timeout.is_some() {
+        own_process_group(&mut shell);
+    }
+
+    let started = Instant::now();
+    let mut child = shell
+        .stderr(stderr)
+        .spawn()
+        .with_context(|| format!("Failed to start {program}"))?;
+    let pid = child.id();
+
+    // A spinner can only share the terminal when all output passes through `tt`.
+    let activity = (capture.stdout.is_some()
+        && capture.stderr_tail.is_some()
+        && std::io::stdout().is_terminal())
+    .then(|| Activity::new(started));
+
+    let (stdout_pipe, stderr_pipe) = (child.stdout.take(), child.stderr.take());
+    let stdout = async {
+        match (stdout_pipe, capture.stdout) {
+            (Some(pipe), Some((head, tail))) => {
+                tee(
+                    pipe,
+                    tokio::io::stdout(),
+                    Kept::new(head, tail),
+                    activity.as_ref(),
+                )
+                .await
+            }
+            _ => Ok(String::new()),
+        }
+    };
+    let stderr = async {
+        match (stderr_pipe, capture.stderr_tail) {
+            (Some(pipe), Some(tail)) => {
+                tee(
+                    pipe,
+                    tokio::io::stderr(),
+                    Kept::new(0, tail),
+                    activity.as_ref(),
+                )
+                .await
+            }
+            _ => Ok(String::new()),
+        }
+    };
+    let finished = async {
+        let (stdout, stderr_tail) = tokio::join!(stdout, stderr);
+        (stdout, stderr_tail, child.wait().await)
+    };
+    tokio::pin!(finished);
+
+    let mut timed_out = None;
+    let run = async {
+        let Some(limit) = timeout else {
+            return (&mut finished).await;
+        };
+        tokio::select! {
+            result = &mut finished => result,
+            _ = tokio::time::sleep(limit) => {
+                timed_out = Some(limit);
+                terminate_group(pid, false);
+                match tokio::time::timeout(KILL_GRACE, &mut finished).await {
+                    Ok(result) => result,
+                    Err(_) => {
+                        terminate_group(pid, true);
+                        (&mut finished).await
+                    }
+                }
+            }
+        }
+    };
+    let (stdout, stderr_tail, status) = match &activity {
+        Some(activity) => {
+            tokio::select! {
+                result = run => result,
+                _ = activity.watch() => unreachable!("the activity watcher never returns"),
+            }
+        }
+        None => run.await,
+    };
+    if let Some(activity) = &activity {
+        drop(activity.quiet().await);
+    }
+    #[cfg(unix)]
+    if timeout.is_some() {
+        reclaim_terminal();
+    }
+
+    Ok(ShellRun {
+        status: status.with_context(|| format!("Failed to wait for {program}"))?,
+        stderr_tail: stderr_tail.context("Failed to read command stderr")?,
+        stdout: stdout.context("Failed to read command stdout")?,
+        timed_out,
+    })
+}
+
+/// Puts the command in a process group of its own, so a timeout can kill
+/// everything it started, and hands that group the terminal.
+#[cfg(unix)]
+fn own_process_group(shell: &mut Command) {
+    let interactive = std::io::stdin().is_terminal();
+    // SAFETY: only async-signal-safe calls run between fork and exec.
+    unsafe {
+        shell.pre_exec(move || {
+            if libc::setpgid(0, 0) != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            if interactive {
+                let previous = libc::signal(libc::SIGTTOU, libc::SIG_IGN);
+                libc::tcsetpgrp(libc::STDIN_FILENO, libc::getpid());
+                libc::signal(libc::SIGTTOU, previous);
+            }
+            Ok(())
+        });
+    }
+}
+
+/// Takes the terminal back from the command's process group.
+#[cfg(unix)]
+fn reclaim_terminal() {
+    if !std::io::stdin().is_terminal() {
+        return;
+    }
+    // SAFETY: plain libc calls on the process's own stdin.
+    unsafe {
+        let previous = libc::signal(libc::SIGTTOU, libc::SIG_IGN);
+        libc::tcsetpgrp(libc::STDIN_FILENO, libc::getpgrp());
+        libc::signal(libc::SIGTTOU, previous);
+    }
+}
+
+#[cfg(unix)]
+fn terminate_group(pid: Option<u32>, force: bool) {
+    let Some(pid) = pid else {
+        return;
+    };
+    let signal = if force { libc::SIGKILL } else { libc::SIGTERM };
+    // SAFETY: the group id is the child's pid, set by `own_process_group`.
+    unsafe {
+        libc::killpg(pid as libc::pid_t, signal);
+    }
+}
+
+#[cfg(not(unix))]
+fn terminate_group(pid: Option<u32>, _force: bool) {
+    let Some(pid) = pid else {
+        return;
+    };
+    let _ = std::process::Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/T", "/F"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+}
+
+/// Parses `30s`, `500ms`, `5m`, `1h` or `1m30s`; a bare number is seconds.
+/// Zero and durations too long to represent are rejected.
+pub fn parse_duration(value: &str) -> std::result::Result<Duration, String> {
+    let value = value.trim();
+    let invalid = || format!("invalid duration `{value}` (expected something like 30s, 5m or 1h)");
+    if value.is_empty() {
+        return Err(invalid());
+    }
+    if value.bytes().all(|byte| byte.is_ascii_digit()) {
+        return parse_duration(&format!("{value}s"));
+    }
+
+    let too_long = || format!("duration `{value}` is too long");
+    let mut total = Duration::ZERO;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .filter(|&end| end > 0)
+            .ok_or_else(invalid)?;
+        let amount: u64 = rest[..digits].parse().map_err(|_| invalid())?;
+        rest = &rest[digits..];
+        let unit_end = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let part = match &rest[..unit_end] {
+            "ms" => Duration::from_millis(amount),
+            "s" => Duration::from_secs(amount),
+            "m" => Duration::from_secs(amount.checked_mul(60).ok_or_else(too_long)?),
+            "h" => Duration::from_secs(amount.checked_mul(3600).ok_or_else(too_long)?),
+            _ => return Err(invalid()),
+        };
+        total = total.checked_add(part).ok_or_else(too_long)?;
+        rest = &rest[unit_end..];
+    }
+    if total.is_zero() {
+        return Err(invalid());
+    }
+    Ok(total)
+}
+
+/// Shows the loader on stdout while a command whose output passes through
+/// `tt` stays silent, and takes it down before the next output is written.
+struct Activity {
+    started: Instant,
+    state: Mutex<ActivityState>,
+}
+
+struct ActivityState {
+    last_output: Instant,
+    /// The cursor is at the start of a line, where the loader can go.
+    at_line_start: bool,
+    loader: Option<LoaderHandle>,
+}
+
+impl Activity {
+    fn new(started: Instant) -> Self {
+        Self {
+            started,
+            state: Mutex::new(ActivityState {
+                last_output: started,
+                at_line_start: true,
+                loader: None,
+            }),
+        }
+    }
+
+    /// Clears the loader; the guard keeps it away while the caller writes.
+    async fn quiet(&self) -> MutexGuard<'_, ActivityState> {
+        let mut state = self.state.lock().await;
+        if let Some(mut loader) = state.loader.take() {
+            loader.stop().await;
+            let _ = execute!(
+                std::io::stdout(),
+                terminal::Clear(terminal::ClearType::CurrentLine),
+                cursor::MoveToColumn(0),
+                cursor::Show
+            );
+        }
+        state
+    }
+
+    async fn watch(&self) {
+        loop {
+            tokio::time::sleep(IDLE_POLL).await;
+            let mut state = self.state.lock().await;
+            if state.loader.is_none()
+                && state.at_line_start
+                && state.last_output.elapsed() >= IDLE_DELAY
+            {
+                state.loader = Some(
+                    ShimmerLoader::new("Still running")
+                        .since(self.started)
+                        .spawn(),
+                );
+            }
+        }
+    }
+}
+
+/// Copies `pipe` to `terminal` as it arrives, keeping what `kept` asks for.
+async fn tee<R, W>(
+    mut pipe: R,
+    mut terminal: W,
+    mut kept: Kept,
+    activity: Option<&Activity>,
+) -> std::io::Result<String>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut buffer = [0u8; 4096];
+    loop {
+        let read = pipe.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        let chunk = &buffer[..read];
+        let guard = match activity {
+            Some(activity) => {
+                let mut state = activity.quiet().await;
+                state.last_output = Instant::now();
+                state.at_line_start = chunk.ends_with(b"\n");
+                Some(state)
+            }
+            None => None,
+        };
+        // The user's terminal going away should not abort the command.
+        let _ = terminal.write_all(chunk).await;
+        let _ = terminal.flush().await;
+        drop(guard);
+        kept.push(chunk);
+    }
+    Ok(kept.finish())
+}
+
+/// The first `head_limit` and last `tail_limit` bytes of a stream.
+struct Kept {
+    head: Vec<u8>,
+    tail: Vec<u8>,
+    head_limit: usize,
+    tail_limit: usize,
+    total: usize,
+}
+
+impl Kept {
+    fn new(head_limit: usize, tail_limit: usize) -> Self {
+        Self {
+            head: Vec::new(),
+            tail: Vec::new(),
+            head_limit,
+            tail_limit,
+            total: 0,
+        }
+    }
+
+    fn push(&mut self, mut bytes: &[u8]) {
+        self.total += bytes.len();
+        let room = self.head_limit - self.head.len();
+        if room > 0 {
+            let taken = room.min(bytes.len());
+            self.head.extend_from_slice(&bytes[..taken]);
+            bytes = &bytes[taken..];
+        }
+        self.tail.extend_from_slice(bytes);
+        if self.tail.len() > self.tail_limit {
+            self.tail.drain(..self.tail.len() - self.tail_limit);
+        }
+    }
+
+    /// Joins head and tail, marking the gap between them when something was
+    /// dropped. Both ends are cut back to whole lines around the gap.
+    fn finish(self) -> String {
+        let kept = self.head.len() + self.tail.len();
+        if self.head_limit == 0 || self.total <= kept {
+            let mut all = self.head;
+            all.extend_from_slice(&self.tail);
+            return String::from_utf8_lossy(&all).into_owned();
+        }
+
+        let head_end = self
+            .head
+            .iter()
+            .rposition(|&byte| byte == b'\n')
+            .map_or(self.head.len(), |index| index + 1);
+        let tail_start = self
+            .tail
+            .iter()
+            .position(|&byte| byte == b'\n')
+            .map_or(0, |index| index + 1);
+        let omitted = self.total - head_end - (self.tail.len() - tail_start);
+        let mut joined = String::from_utf8_lossy(&self.head[..head_end]).into_owned();
+        if !joined.is_empty() && !joined.ends_with('\n') {
+            joined.push('\n');
+        }
+        joined.push_str(&format!("[… {omitted} bytes omitted …]\n"));
+        joined.push_str(&String::from_utf8_lossy(&self.tail[tail_start..]));
+        joined
+    }
+}
+
+/// Parses `command` with the shell that `run_in_shell` would use, without
+/// executing it.
+pub async fn check_syntax(command: &str) -> SyntaxCheck {
+    let (program, kind) = resolve_shell();
+    check_syntax_with(&program, kind, command).await
+}
+
+pub async fn check_syntax_with(program: &str, kind: ShellKind, command: &str) -> SyntaxCheck {
+    let Some(flag) = kind.parse_only_flag() else {
+        return SyntaxCheck::Unavailable;
+    };
+
+    let output = Command::new(program)
+        .arg(flag)
+        .arg(kind.command_flag())
+        .arg(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .output();
+    let Ok(Ok(output)) = tokio::time::timeout(SYNTAX_CHECK_TIMEOUT, output).await else {
+        return SyntaxCheck::Unavailable;
+    };
+    if output.status.success() {
+        return SyntaxCheck::Valid;
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let message = stderr
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(|line| strip_shell_prefix(line, program))
+        .unwrap_or("the shell could not parse the command");
+    SyntaxCheck::Invalid(message.to_string())
+}
+
+/// Drops the `bash: -c: ` or `zsh:1: ` noise shells put before the message.
+fn strip_shell_prefix<'a>(line: &'a str, program: &str) -> &'a str {
+    let name = program.rsplit(['/', '\\']).next().unwrap_or(program);
+    let Some(rest) = line.strip_prefix(name) else {
+        return line;
+    };
+    let rest = rest.trim_start_matches(|c: char| c == ':' || c == ' ' || c.is_ascii_digit());
+    rest.strip_prefix("-c:")
+        .map(str::trim_start)
+        .unwrap_or(rest)
+}
+
+/// Maps a child's exit status onto the exit code `tt` should report, using the
+/// shell convention of `128 + signal` for processes killed by a signal.
+pub fn exit_code(status: &ExitStatus) -> ExitCode {
+    if let Some(code) = status.code() {
+        return ExitCode::from(code.clamp(0, 255) as u8);
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return ExitCode::from((128 + signal).clamp(0, 255) as u8);
+        }
+    }
+
+    ExitCode::FAILURE
+}
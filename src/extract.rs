@@ -0,0 +1,543 @@
+use crate::shell::{self, List, MAX_NESTING, RedirectOp};
+use std::collections::VecDeque;
+
+const SHELL_LANGUAGES: &[&str] = &[
+    "sh",
+    "bash",
+    "zsh",
+    "fish",
+    "ksh",
+    "shell",
+    "console",
+    "shell-session",
+    "shellsession",
+    "terminal",
+    "powershell",
+    "pwsh",
+    "ps1",
+    "cmd",
+    "bat",
+    "batch",
+];
+
+const SESSION_LANGUAGES: &[&str] = &["console", "shell-session", "shellsession", "terminal"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CandidateKind {
+    Fenced,
+    Inline,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandCandidate {
+    pub command: String,
+    /// Language tag of the surrounding fence, lowercased (`None` for inline spans
+    /// and untagged fences).
+    pub language: Option<String>,
+    pub multiline: bool,
+    pub kind: CandidateKind,
+}
+
+impl CommandCandidate {
+    fn new(command: String, language: Option<String>, kind: CandidateKind) -> Self {
+        let multiline = command.contains('\n');
+        Self {
+            command,
+            language,
+            multiline,
+            kind,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Extraction {
+    /// Every command-looking block or span, in the order it appeared.
+    pub candidates: Vec<CommandCandidate>,
+    /// The prose around the command(s), with command-only lines removed.
+    pub explanation: String,
+    primary: Option<usize>,
+}
+
+impl Extraction {
+    /// The command `tt` should act on, if the response contained one.
+    pub fn primary(&self) -> Option<&CommandCandidate> {
+        self.primary.and_then(|index| self.candidates.get(index))
+    }
+
+    pub fn primary_command(&self) -> Option<&str> {
+        self.primary().map(|candidate| candidate.command.as_str())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.candidates.is_empty()
+    }
+}
+
+/// One entry of a numbered list of candidate commands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alternative {
+    pub command: String,
+    /// The one-line trade-off the model gave for this command.
+    pub tradeoff: String,
+}
+
+/// One step of a multi-command plan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlanStep {
+    pub command: String,
+    /// What the step is for, as the model described it.
+    pub purpose: String,
+}
+
+/// Pulls commands back out of a markdown response. Fenced blocks win over
+/// inline spans; when several blocks compete, the last one is the answer the
+/// model ended on.
+pub fn extract(response: &str) -> Extraction {
+    let text = strip_reasoning(response);
+    let (fenced, prose_lines) = scan_fences(&text);
+
+    if !fenced.is_empty() {
+        let primary = Some(fenced.len() - 1);
+        return Extraction {
+            candidates: fenced,
+            explanation: tidy_explanation(&prose_lines),
+            primary,
+        };
+    }
+
+    let prose = prose_lines.join("\n");
+    let candidates: Vec<CommandCandidate> = inline_spans(&prose)
+        .into_iter()
+        .map(|span| strip_prompt(span.trim()).to_string())
+        .filter(|span| looks_like_command(span))
+        .map(|span| CommandCandidate::new(span, None, CandidateKind::Inline))
+        .collect();
+
+    let primary = candidates
+        .iter()
+        .rposition(|candidate| candidate.command.contains(char::is_whitespace))
+        .or_else(|| candidates.len().checked_sub(1));
+
+    let explanation_lines: Vec<&str> = prose_lines
+        .into_iter()
+        .filter(|line| !is_code_only_line(line))
+        .collect();
+
+    Extraction {
+        candidates,
+        explanation: tidy_explanation(&explanation_lines),
+        primary,
+    }
+}
+
+/// Parses a numbered list of alternatives, one command per item. Items without
+/// a command are skipped, as are repeats of an earlier command.
+pub fn extract_alternatives(response: &str) -> Vec<Alternative> {
+    let mut alternatives: Vec<Alternative> = Vec::new();
+    for (command, tradeoff) in numbered_commands(response) {
+        if alternatives.iter().any(|seen| seen.command == command) {
+            continue;
+        }
+        alternatives.push(Alternative { command, tradeoff });
+    }
+    alternatives
+}
+
+/// Parses a numbered list of plan steps in order. Unlike alternatives,
+/// repeated commands are kept since a plan may need them twice.
+pub fn extract_plan(response: &str) -> Vec<PlanStep> {
+    numbered_commands(response)
+        .into_iter()
+        .map(|(command, purpose)| PlanStep { command, purpose })
+        .collect()
+}
+
+/// The command and the first line of description of each numbered list item
+/// that contains a command.
+fn numbered_commands(response: &str) -> Vec<(String, String)> {
+    let text = strip_reasoning(response);
+    let mut items: Vec<Vec<&str>> = Vec::new();
+
+    for line in text.lines() {
+        match list_item_start(line) {
+            Some(rest) => items.push(vec![rest]),
+            None => {
+                if let Some(item) = items.last_mut() {
+                    item.push(line);
+                }
+            }
+        }
+    }
+
+    let mut commands = Vec::new();
+    for item in items {
+        let body = item.join("\n");
+        let extraction = extract(&body);
+        let Some(command) = extraction.primary_command() else {
+            continue;
+        };
+
+        let description = extraction
+            .explanation
+            .lines()
+            .map(|line| describe_without(line, command))
+            .find(|line| !line.is_empty())
+            .unwrap_or_default();
+        commands.push((command.to_string(), description));
+    }
+
+    commands
+}
+
+/// The text after `1.` or `1)` when `line` opens a numbered list item.
+fn list_item_start(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    let digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+    if digits == 0 {
+        return None;
+    }
+    let rest = trimmed[digits..].strip_prefix(['.', ')'])?;
+    rest.starts_with(char::is_whitespace).then(|| rest.trim())
+}
+
+/// A list item line with the command's code span and the separators around
+/// it removed, leaving just the description.
+fn describe_without(line: &str, command: &str) -> String {
+    let mut line = line.trim().to_string();
+    for span in [format!("`{command}`"), format!("`` {command} ``")] {
+        line = line.replacen(&span, "", 1);
+    }
+
+    let is_separator = |c: char| c.is_whitespace() || matches!(c, '-' | '–' | '—' | ':');
+    line.trim_matches(is_separator)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Drops `<think>…</think>` reasoning that some local models stream before
+/// their answer. An unterminated block means the model never got to answering.
+fn strip_reasoning(response: &str) -> String {
+    let mut remaining = response;
+    let mut output = String::with_capacity(response.len());
+
+    while let Some(start) = remaining.find("<think>") {
+        output.push_str(&remaining[..start]);
+        match remaining[start..].find("</think>") {
+            Some(end) => remaining = &remaining[start + end + "</think>".len()..],
+            None => return output,
+        }
+    }
+
+    output.push_str(remaining);
+    output
+}
+
+struct OpenFence {
+    marker: char,
+    width: usize,
+    indent: usize,
+    language: Option<String>,
+    lines: Vec<String>,
+    /// Heredocs opened on earlier lines whose bodies have not ended, in the
+    /// order the shell reads them.
+    heredocs: VecDeque<Heredoc>,
+}
+
+impl OpenFence {
+    fn is_shell(&self) -> bool {
+        self.language
+            .as_deref()
+            .is_none_or(|language| SHELL_LANGUAGES.contains(&language))
+    }
+
+    fn is_closed_by(&self, line: &str) -> bool {
+        if !self.heredocs.is_empty() {
+            return false;
+        }
+        let trimmed = line.trim();
+        let width = trimmed.chars().take_while(|c| *c == self.marker).count();
+        width >= self.width && width == trimmed.chars().count()
+    }
+
+    fn push(&mut self, line: &str) {
+        let stripped = strip_indent(line, self.indent);
+
+        if self.is_shell() {
+            match self.heredocs.front() {
+                Some(heredoc) if heredoc.is_terminated_by(stripped) => {
+                    self.heredocs.pop_front();
+                }
+                Some(_) => {}
+                None => self.heredocs = Heredoc::opened_by(stripped).into(),
+            }
+        }
+
+        self.lines.push(stripped.to_string());
+    }
+
+    fn into_candidate(self) -> Option<CommandCandidate> {
+        if !self.is_shell() {
+            return None;
+        }
+
+        let is_session = self
+            .language
+            .as_deref()
+            .is_some_and(|language| SESSION_LANGUAGES.contains(&language))
+            || self
+                .lines
+                .iter()
+                .find(|line| !line.trim().is_empty())
+                .is_some_and(|line| line.starts_with("$ "));
+
+        let body = if is_session {
+            session_commands(&self.lines)
+        } else {
+            self.lines.join("\n")
+        };
+
+        let command = body.trim_matches('\n').trim_end().to_string();
+        if command.trim().is_empty() {
+            return None;
+        }
+
+        Some(CommandCandidate::new(
+            command,
+            self.language,
+            CandidateKind::Fenced,
+        ))
+    }
+}
+
+struct Heredoc {
+    delimiter: String,
+    strip_tabs: bool,
+}
+
+impl Heredoc {
+    /// Every heredoc `line` opens, including those inside substitutions.
+    fn opened_by(line: &str) -> Vec<Self> {
+        let mut found = Vec::new();
+        collect_heredocs(&shell::parse_partial(line), 0, &mut found);
+        found
+    }
+
+    fn is_terminated_by(&self, line: &str) -> bool {
+        let candidate = if self.strip_tabs {
+            line.trim_start_matches('\t')
+        } else {
+            line
+        };
+        candidate.trim_end() == self.delimiter
+    }
+}
+
+fn collect_heredocs(list: &List, depth: usize, found: &mut Vec<Heredoc>) {
+    if depth > MAX_NESTING {
+        return;
+    }
+
+    for redirect in list.redirects() {
+        if matches!(
+            redirect.op,
+            RedirectOp::Heredoc | RedirectOp::HeredocStripTabs
+        ) {
+            found.push(Heredoc {
+                delimiter: redirect.target.text.clone(),
+                strip_tabs: redirect.op == RedirectOp::HeredocStripTabs,
+            });
+        }
+    }
+    for substitution in list.substitutions() {
+        collect_heredocs(&substitution.body, depth + 1, found);
+    }
+}
+
+fn scan_fences(text: &str) -> (Vec<CommandCandidate>, Vec<&str>) {
+    let mut candidates = Vec::new();
+    let mut prose = Vec::new();
+    let mut open: Option<OpenFence> = None;
+
+    for line in text.lines() {
+        if let Some(fence) = open.as_mut() {
+            if fence.is_closed_by(line) {
+                if let Some(candidate) = open.take().and_then(OpenFence::into_candidate) {
+                    candidates.push(candidate);
+                }
+            } else {
+                fence.push(line);
+            }
+            continue;
+        }
+
+        match parse_fence_open(line) {
+            Some(fence) => open = Some(fence),
+            None => prose.push(line),
+        }
+    }
+
+    // A response cut off mid-stream still leaves a usable block behind.
+    if let Some(candidate) = open.and_then(OpenFence::into_candidate) {
+        candidates.push(candidate);
+    }
+
+    (candidates, prose)
+}
+
+fn parse_fence_open(line: &str) -> Option<OpenFence> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    let trimmed = &line[indent..];
+    let marker = trimmed.chars().next().filter(|c| matches!(c, '`' | '~'))?;
+    let width = trimmed.chars().take_while(|c| *c == marker).count();
+    if width < 3 {
+        return None;
+    }
+
+    let info = trimmed[width..].trim();
+    // "```ls -la```" on one line is an inline span, not a fence.
+    if marker == '`' && info.contains('`') {
+        return None;
+    }
+
+    let language = info
+        .split(|c: char| c.is_whitespace() || c == '{' || c == ',')
+        .next()
+        .map(|word| word.trim_start_matches('.').to_ascii_lowercase())
+        .filter(|word| !word.is_empty());
+
+    Some(OpenFence {
+        marker,
+        width,
+        indent,
+        language,
+        lines: Vec::new(),
+        heredocs: VecDeque::new(),
+    })
+}
+
+fn strip_indent(line: &str, indent: usize) -> &str {
+    let leading = line.len() - line.trim_start_matches(' ').len();
+    &line[leading.min(indent)..]
+}
+
+/// Keeps only the prompted lines of a terminal transcript (plus their `\`
+/// continuations), dropping the sample output between them.
+fn session_commands(lines: &[String]) -> String {
+    let mut commands = Vec::new();
+    let mut continuing = false;
+
+    for line in lines {
+        if continuing {
+            commands.push(line.as_str());
+        } else if let Some(command) = line.strip_prefix("$ ") {
+            commands.push(command);
+        } else {
+            continue;
+        }
+        continuing = line.trim_end().ends_with('\\');
+    }
+
+    commands.join("\n")
+}
+
+/// CommonMark code spans: a run of N backticks closes at the next run of
+/// exactly N backticks.
+fn inline_spans(text: &str) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut spans = Vec::new();
+    let mut index = 0;
+
+    while index < chars.len() {
+        if chars[index] != '`' {
+            index += 1;
+            continue;
+        }
+
+        let open_start = index;
+        while index < chars.len() && chars[index] == '`' {
+            index += 1;
+        }
+        let width = index - open_start;
+
+        let mut cursor = index;
+        let mut close = None;
+        while cursor < chars.len() {
+            if chars[cursor] == '`' {
+                let run_start = cursor;
+                while cursor < chars.len() && chars[cursor] == '`' {
+                    cursor += 1;
+                }
+                if cursor - run_start == width {
+                    close = Some(run_start);
+                    break;
+                }
+            } else {
+                cursor += 1;
+            }
+        }
+
+        if let Some(close) = close {
+            let content: String = chars[index..close]
+                .iter()
+                .map(|c| if *c == '\n' { ' ' } else { *c })
+                .collect();
+            let content = if content.len() > 2
+                && content.starts_with(' ')
+                && content.ends_with(' ')
+                && !content.trim().is_empty()
+            {
+                content[1..content.len() - 1].to_string()
+            } else {
+                content
+            };
+            spans.push(content);
+            index = close + width;
+        }
+    }
+
+    spans
+}
+
+fn strip_prompt(command: &str) -> &str {
+    command.strip_prefix("$ ").unwrap_or(command).trim()
+}
+
+/// Inline spans in prose are often flags or file names rather than commands.
+fn looks_like_command(span: &str) -> bool {
+    !span.is_empty() && !span.starts_with('-')
+}
+
+fn is_code_only_line(line: &str) -> bool {
+    let trimmed = line.trim();
+    if !trimmed.starts_with('`') {
+        return false;
+    }
+
+    let spans = inline_spans(trimmed);
+    if spans.is_empty() {
+        return false;
+    }
+
+    let mut remainder = trimmed.to_string();
+    for span in &spans {
+        remainder = remainder.replacen(span.as_str(), "", 1);
+    }
+    remainder
+        .chars()
+        .all(|c| c == '`' || c.is_whitespace() || matches!(c, '.' | ',' | ';' | ':'))
+}
+
+fn tidy_explanation(lines: &[&str]) -> String {
+    let mut output: Vec<&str> = Vec::new();
+    for line in lines {
+        let line = line.trim_end();
+        if line.trim().is_empty() && output.last().is_none_or(|last| last.trim().is_empty()) {
+            continue;
+        }
+        output.push(line);
+    }
+
+    output.join("\n").trim().to_string()
+}
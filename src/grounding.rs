@@ -0,0 +1,288 @@
+use crate::typo::edit_distance;
+use ignore::WalkBuilder;
+use std::path::{Path, PathBuf};
+
+/// The walk stops at whichever limit comes first, so a huge tree costs the
+/// same as a modest one.
+const MAX_DEPTH: usize = 4;
+const MAX_ENTRIES: usize = 5_000;
+const MAX_PATHS_PER_TERM: usize = 3;
+const MAX_TERMS: usize = 6;
+/// A term that loosely matches more entries than this is too generic to say
+/// which one the user meant.
+const MAX_LOOSE_MATCHES: usize = 8;
+
+/// Words that never name a file, so they are not looked up.
+const STOPWORDS: &[&str] = &[
+    "a",
+    "about",
+    "all",
+    "an",
+    "and",
+    "any",
+    "are",
+    "as",
+    "at",
+    "be",
+    "by",
+    "can",
+    "compress",
+    "copy",
+    "count",
+    "create",
+    "delete",
+    "directory",
+    "dir",
+    "do",
+    "each",
+    "edit",
+    "every",
+    "file",
+    "files",
+    "find",
+    "folder",
+    "folders",
+    "for",
+    "from",
+    "get",
+    "how",
+    "in",
+    "into",
+    "is",
+    "it",
+    "its",
+    "last",
+    "list",
+    "make",
+    "me",
+    "move",
+    "my",
+    "new",
+    "of",
+    "on",
+    "one",
+    "open",
+    "or",
+    "out",
+    "print",
+    "remove",
+    "rename",
+    "run",
+    "search",
+    "show",
+    "size",
+    "so",
+    "than",
+    "that",
+    "the",
+    "them",
+    "then",
+    "this",
+    "to",
+    "up",
+    "what",
+    "where",
+    "which",
+    "with",
+    "without",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Want {
+    Any,
+    File,
+    Dir,
+}
+
+/// A word from the question and the real paths it most likely refers to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mention {
+    pub term: String,
+    /// Relative to the working directory; directories end with `/`.
+    pub paths: Vec<PathBuf>,
+}
+
+pub fn ground(question: &str) -> Vec<Mention> {
+    match std::env::current_dir() {
+        Ok(dir) => ground_in(&dir, question),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Matches the nouns in `question` against a bounded, `.gitignore`-aware
+/// walk of `dir`. Exact names beat stems, stems beat prefixes, and close
+/// typos come last; shallower paths win ties.
+pub fn ground_in(dir: &Path, question: &str) -> Vec<Mention> {
+    let terms = terms(question);
+    if terms.is_empty() {
+        return Vec::new();
+    }
+    let entries = walk(dir);
+
+    terms
+        .into_iter()
+        .filter_map(|(term, want)| {
+            let paths = best_matches(&entries, &term, want);
+            (!paths.is_empty()).then_some(Mention { term, paths })
+        })
+        .collect()
+}
+
+/// Lines for the system prompt, or an empty string when nothing matched.
+pub fn prompt_block(mentions: &[Mention]) -> String {
+    if mentions.is_empty() {
+        return String::new();
+    }
+
+    let lines: Vec<String> = mentions
+        .iter()
+        .map(|mention| {
+            let paths: Vec<String> = mention
+                .paths
+                .iter()
+                .map(|path| format!("`{}`", path.display()))
+                .collect();
+            format!("- \"{}\": {}", mention.term, paths.join(", "))
+        })
+        .collect();
+    format!(
+        "\n\nPaths in the working directory that match words in the request (use these exact paths when the request refers to them):\n{}",
+        lines.join("\n")
+    )
+}
+
+struct Entry {
+    path: PathBuf,
+    name: String,
+    depth: usize,
+    is_dir: bool,
+}
+
+fn walk(dir: &Path) -> Vec<Entry> {
+    WalkBuilder::new(dir)
+        .max_depth(Some(MAX_DEPTH))
+        .require_git(false)
+        .build()
+        .flatten()
+        .filter(|entry| entry.depth() > 0)
+        .take(MAX_ENTRIES)
+        .filter_map(|entry| {
+            let path = entry.path().strip_prefix(dir).ok()?.to_path_buf();
+            Some(Entry {
+                name: entry.file_name().to_string_lossy().to_lowercase(),
+                depth: entry.depth(),
+                is_dir: entry.file_type().is_some_and(|kind| kind.is_dir()),
+                path,
+            })
+        })
+        .collect()
+}
+
+/// Candidate words, with a hint when the next word says "folder" or "file".
+fn terms(question: &str) -> Vec<(String, Want)> {
+    let words: Vec<String> = question
+        .split_whitespace()
+        .map(|word| {
+            word.trim_matches(|c: char| !c.is_alphanumeric() && !matches!(c, '.' | '_' | '-' | '/'))
+                .trim_end_matches('.')
+                .to_lowercase()
+        })
+        .collect();
+
+    let mut terms: Vec<(String, Want)> = Vec::new();
+    for (index, word) in words.iter().enumerate() {
+        if word.chars().count() < 3 || STOPWORDS.contains(&word.as_str()) {
+            continue;
+        }
+        if terms.iter().any(|(seen, _)| seen == word) {
+            continue;
+        }
+        let want = match words.get(index + 1).map(String::as_str) {
+            Some("folder" | "folders" | "directory" | "dir") => Want::Dir,
+            Some("file" | "files") => Want::File,
+            _ => Want::Any,
+        };
+        terms.push((word.clone(), want));
+        if terms.len() == MAX_TERMS {
+            break;
+        }
+    }
+    terms
+}
+
+fn best_matches(entries: &[Entry], term: &str, want: Want) -> Vec<PathBuf> {
+    let mut scored: Vec<(u32, &Entry)> = entries
+        .iter()
+        .filter(|entry| match want {
+            Want::Any => true,
+            Want::File => !entry.is_dir,
+            Want::Dir => entry.is_dir,
+        })
+        .filter_map(|entry| score(&entry.name, term).map(|score| (score, entry)))
+        .collect();
+
+    let loose = scored.iter().filter(|(score, _)| *score < 90).count();
+    if loose > MAX_LOOSE_MATCHES {
+        scored.retain(|(score, _)| *score >= 90);
+    }
+    scored.sort_by(|(a, left), (b, right)| {
+        b.cmp(a)
+            .then(left.depth.cmp(&right.depth))
+            .then(left.path.cmp(&right.path))
+    });
+
+    scored
+        .into_iter()
+        .take(MAX_PATHS_PER_TERM)
+        .map(|(_, entry)| {
+            if entry.is_dir {
+                PathBuf::from(format!("{}/", entry.path.display()))
+            } else {
+                entry.path.clone()
+            }
+        })
+        .collect()
+}
+
+fn score(name: &str, term: &str) -> Option<u32> {
+    let stem = name.split('.').next().unwrap_or(name);
+    if name == term {
+        Some(100)
+    } else if stem == term || stem.trim_end_matches('s') == term.trim_end_matches('s') {
+        Some(90)
+    } else if name.starts_with(term) {
+        Some(70)
+    } else if term.len() >= 4 && name.contains(term) {
+        Some(50)
+    } else if term.len() >= 4 && edit_distance(stem, term) <= (term.len() / 5).max(1) {
+        Some(40)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn terms_skip_stopwords_and_note_the_kind() {
+        assert_eq!(
+            terms("Compress the logs folder and open the README."),
+            vec![
+                ("logs".to_string(), Want::Dir),
+                ("readme".to_string(), Want::Any)
+            ]
+        );
+    }
+
+    #[test]
+    fn scores_rank_exact_over_loose_matches() {
+        assert_eq!(score("readme.md", "readme"), Some(90));
+        assert_eq!(score("logs", "logs"), Some(100));
+        assert_eq!(score("log", "logs"), Some(90));
+        assert_eq!(score("config.yaml", "confg"), Some(40));
+        assert_eq!(score("readme.md", "raedme"), Some(40));
+        assert_eq!(score("src", "logs"), None);
+    }
+}
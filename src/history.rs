@@ -0,0 +1,85 @@
+use crate::config::Config;
+use crate::snapshot::Snapshot;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// One command `tt` ran, stored as a line of `history.jsonl`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    pub question: String,
+    pub model: String,
+    /// The command as the model suggested it.
+    pub suggested: String,
+    /// The command that actually ran, after any edits.
+    pub executed: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    /// State just before the command ran, for `tt undo`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<Snapshot>,
+}
+
+impl HistoryEntry {
+    pub fn new(question: &str, model: &str, suggested: &str, executed: &str) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+        Self {
+            timestamp,
+            question: question.to_string(),
+            model: model.to_string(),
+            suggested: suggested.to_string(),
+            executed: executed.to_string(),
+            exit_code: None,
+            snapshot: None,
+        }
+    }
+
+    /// Whether the user changed the suggestion before running it.
+    pub fn was_edited(&self) -> bool {
+        self.suggested.trim() != self.executed.trim()
+    }
+}
+
+pub fn history_path() -> Result<PathBuf> {
+    Ok(Config::config_dir()?.join("history.jsonl"))
+}
+
+pub fn append(entry: &HistoryEntry) -> Result<()> {
+    append_to(&history_path()?, entry)
+}
+
+pub fn append_to(path: &Path, entry: &HistoryEntry) -> Result<()> {
+    let line = serde_json::to_string(entry).context("Could not serialize history entry")?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .context("Could not open history file")?;
+    writeln!(file, "{line}").context("Could not write history entry")
+}
+
+pub fn load() -> Result<Vec<HistoryEntry>> {
+    load_from(&history_path()?)
+}
+
+/// Reads every entry, oldest first. Lines that do not parse (say, from a
+/// write cut short) are skipped rather than failing the whole file.
+pub fn load_from(path: &Path) -> Result<Vec<HistoryEntry>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let contents = fs::read_to_string(path).context("Could not read history file")?;
+    Ok(contents
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
@@ -0,0 +1,612 @@
+use crate::shell::{self, Word, WordPart};
+use glob::{MatchOptions, Pattern};
+use similar::TextDiff;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command;
+
+/// Listing stops here so a sweeping `rm -r` cannot stall the prompt.
+const MAX_FILES: usize = 100_000;
+const MAX_DIFFS: usize = 3;
+const MAX_DIFF_CANDIDATES: usize = 20;
+const MAX_DIFF_LINES: usize = 40;
+const DRY_RUN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Programs that make a `find -exec` destructive.
+const DESTRUCTIVE_EXEC: &[&str] = &[
+    "rm", "rmdir", "unlink", "shred", "mv", "chmod", "chown", "chgrp", "truncate",
+];
+
+/// What one destructive command in a line would touch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Impact {
+    /// The program and mode, such as `rm -r` or `find -delete`.
+    pub action: String,
+    /// Existing paths the command acts on, as the command names them.
+    pub files: Vec<PathBuf>,
+    /// Set when listing stopped at the cap, so `files` is only a prefix.
+    pub truncated: bool,
+    /// Unified diffs for the first few files a `sed -i` would change.
+    pub diffs: Vec<String>,
+}
+
+impl Impact {
+    fn new(action: impl Into<String>, (files, truncated): (Vec<PathBuf>, bool)) -> Self {
+        Self {
+            action: action.into(),
+            files,
+            truncated,
+            diffs: Vec::new(),
+        }
+    }
+}
+
+pub async fn assess(command: &str) -> Vec<Impact> {
+    match std::env::current_dir() {
+        Ok(dir) => assess_in(&dir, command).await,
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Works out the targets of each `rm`, `mv`, `chmod`, `sed -i` and
+/// destructive `find` in `command`, resolving paths against `dir`. Commands
+/// whose targets hang on variables, substitutions or piped input are skipped,
+/// since those cannot be known without running anything.
+pub async fn assess_in(dir: &Path, command: &str) -> Vec<Impact> {
+    let mut impacts = Vec::new();
+
+    let list = shell::parse_partial(command);
+    for stage in list.stages() {
+        let (wrappers, args) = shell::unwrap_wrappers(&stage.command.words);
+        let Some(program) = args.first() else {
+            continue;
+        };
+        let name = shell::program_name(&program.text);
+        if matches!(name.as_str(), "cd" | "pushd" | "popd") {
+            // Everything after this resolves against another directory.
+            break;
+        }
+        if wrappers.iter().any(|wrapper| wrapper == "xargs") {
+            continue;
+        }
+        let Some(words) = expand_words(dir, &args[1..]) else {
+            continue;
+        };
+
+        let impact = match name.as_str() {
+            "rm" => removal(dir, &words),
+            "mv" => moved(dir, &words),
+            "chmod" => mode_change(dir, &words),
+            "sed" => in_place_edit(dir, &words).await,
+            "find" => found(dir, &words).await,
+            _ => None,
+        };
+        impacts.extend(impact);
+    }
+
+    impacts
+}
+
+fn removal(dir: &Path, words: &[String]) -> Option<Impact> {
+    let (options, operands) = split_args(words, &[]);
+    if operands.is_empty() {
+        return None;
+    }
+    let recursive = options.iter().any(|(option, _)| {
+        *option == "--recursive" || (!option.starts_with("--") && option.contains(['r', 'R']))
+    });
+    let action = if recursive { "rm -r" } else { "rm" };
+    Some(Impact::new(action, existing(dir, &operands, recursive)))
+}
+
+fn moved(dir: &Path, words: &[String]) -> Option<Impact> {
+    let (options, operands) = split_args(words, &["-t", "-S", "--target-directory", "--suffix"]);
+    let has_target = options
+        .iter()
+        .any(|(option, _)| matches!(*option, "-t" | "--target-directory"));
+    let sources = if has_target {
+        &operands[..]
+    } else {
+        operands.split_last().map(|(_, sources)| sources)?
+    };
+    if sources.is_empty() {
+        return None;
+    }
+    Some(Impact::new("mv", existing(dir, sources, false)))
+}
+
+fn mode_change(dir: &Path, words: &[String]) -> Option<Impact> {
+    let mut recursive = false;
+    let mut reference = false;
+    let mut operands = Vec::new();
+    let mut only_operands = false;
+    let mut iter = words.iter();
+
+    while let Some(word) = iter.next() {
+        let text = word.as_str();
+        if only_operands || !text.starts_with('-') || text == "-" || is_symbolic_mode(text) {
+            operands.push(text);
+        } else if text == "--" {
+            only_operands = true;
+        } else if text == "--recursive" {
+            recursive = true;
+        } else if text.starts_with("--reference") {
+            reference = true;
+            if !text.contains('=') {
+                iter.next();
+            }
+        } else if !text.starts_with("--") && text.contains('R') {
+            recursive = true;
+        }
+    }
+
+    // Without --reference the first operand is the mode.
+    let targets = if reference {
+        &operands[..]
+    } else {
+        operands.get(1..)?
+    };
+    if targets.is_empty() {
+        return None;
+    }
+    let action = if recursive { "chmod -R" } else { "chmod" };
+    Some(Impact::new(action, existing(dir, targets, recursive)))
+}
+
+/// `chmod -w file` removes a permission rather than passing an option.
+fn is_symbolic_mode(text: &str) -> bool {
+    text.strip_prefix('-')
+        .is_some_and(|mode| !mode.is_empty() && mode.chars().all(|c| "rwxXst".contains(c)))
+}
+
+async fn in_place_edit(dir: &Path, words: &[String]) -> Option<Impact> {
+    let mut in_place = false;
+    let mut has_script = false;
+    // `--sandbox` makes GNU sed refuse the `e`, `r` and `w` commands, so the
+    // dry run below cannot touch anything.
+    let mut dry_args = vec!["--sandbox".to_string()];
+    let mut operands = Vec::new();
+    let mut only_operands = false;
+    let mut iter = words.iter().peekable();
+
+    while let Some(word) = iter.next() {
+        if only_operands || !word.starts_with('-') || word == "-" {
+            operands.push(word.as_str());
+            continue;
+        }
+        if word == "--" {
+            only_operands = true;
+            continue;
+        }
+
+        if let Some(long) = word.strip_prefix("--") {
+            let (name, value) = match long.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (long, None),
+            };
+            match name {
+                "in-place" => in_place = true,
+                "expression" | "file" | "line-length" => {
+                    has_script |= name != "line-length";
+                    let value = value.or_else(|| iter.next().cloned())?;
+                    dry_args.push(format!("--{name}={value}"));
+                }
+                "debug" => {}
+                _ => dry_args.push(word.clone()),
+            }
+            continue;
+        }
+
+        for (index, flag) in word.char_indices().skip(1) {
+            let rest = &word[index + flag.len_utf8()..];
+            match flag {
+                'i' => {
+                    in_place = true;
+                    // BSD sed spells "no backup" as a separate empty argument.
+                    if rest.is_empty() && iter.peek().is_some_and(|next| next.is_empty()) {
+                        iter.next();
+                    }
+                    break;
+                }
+                'e' | 'f' | 'l' => {
+                    has_script |= flag != 'l';
+                    let value = if rest.is_empty() {
+                        iter.next()?.clone()
+                    } else {
+                        rest.to_string()
+                    };
+                    dry_args.push(format!("-{flag}"));
+                    dry_args.push(value);
+                    break;
+                }
+                _ => dry_args.push(format!("-{flag}")),
+            }
+        }
+    }
+
+    if !in_place {
+        return None;
+    }
+    if !has_script {
+        if operands.is_empty() {
+            return None;
+        }
+        dry_args.push("-e".to_string());
+        dry_args.push(operands.remove(0).to_string());
+    }
+
+    let files: Vec<&str> = operands
+        .into_iter()
+        .filter(|file| dir.join(file).is_file())
+        .collect();
+    if files.is_empty() {
+        return None;
+    }
+
+    let mut impact = Impact::new("sed -i", existing(dir, &files, false));
+    for file in files.iter().take(MAX_DIFF_CANDIDATES) {
+        if let Some(diff) = sed_diff(dir, &dry_args, file).await {
+            impact.diffs.push(diff);
+            if impact.diffs.len() == MAX_DIFFS {
+                break;
+            }
+        }
+    }
+    Some(impact)
+}
+
+/// Runs the edit without `-i` and diffs its output against the file.
+async fn sed_diff(dir: &Path, args: &[String], file: &str) -> Option<String> {
+    let output = Command::new("sed")
+        .args(args)
+        .arg("--")
+        .arg(file)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .output();
+    let output = tokio::time::timeout(DRY_RUN_TIMEOUT, output)
+        .await
+        .ok()?
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let before = fs::read_to_string(dir.join(file)).ok()?;
+    let after = String::from_utf8(output.stdout).ok()?;
+    if before == after {
+        return None;
+    }
+
+    let diff = TextDiff::from_lines(&before, &after)
+        .unified_diff()
+        .context_radius(2)
+        .header(&format!("a/{file}"), &format!("b/{file}"))
+        .to_string();
+    let mut lines: Vec<&str> = diff.lines().collect();
+    if lines.len() > MAX_DIFF_LINES {
+        lines.truncate(MAX_DIFF_LINES);
+        lines.push("…");
+    }
+    Some(lines.join("\n"))
+}
+
+/// Runs the `find` with its destructive action swapped for `-print0`, which
+/// matches exactly the paths the action would have been applied to.
+async fn found(dir: &Path, words: &[String]) -> Option<Impact> {
+    let mut args = Vec::new();
+    let mut action = None;
+    let mut iter = words.iter();
+
+    while let Some(word) = iter.next() {
+        match word.as_str() {
+            "-delete" => {
+                action.get_or_insert_with(|| "find -delete".to_string());
+                args.push("-print0".to_string());
+            }
+            "-exec" | "-execdir" => {
+                let program = iter.next()?;
+                for part in iter.by_ref() {
+                    if part == ";" || part == "+" {
+                        break;
+                    }
+                }
+                let program = shell::program_name(program);
+                if !DESTRUCTIVE_EXEC.contains(&program.as_str()) {
+                    return None;
+                }
+                action.get_or_insert_with(|| format!("find {word} {program}"));
+                args.push("-print0".to_string());
+            }
+            "-ok" | "-okdir" | "-fprint" | "-fprint0" | "-fprintf" | "-fls" => return None,
+            "-print" | "-print0" | "-ls" => args.push("-true".to_string()),
+            "-printf" => {
+                iter.next();
+                args.push("-true".to_string());
+            }
+            _ => args.push(word.clone()),
+        }
+    }
+    let action = action?;
+
+    let output = Command::new("find")
+        .args(&args)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .output();
+    let output = tokio::time::timeout(DRY_RUN_TIMEOUT, output)
+        .await
+        .ok()?
+        .ok()?;
+    if !output.status.success() && output.stdout.is_empty() {
+        return None;
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut files: Vec<PathBuf> = stdout
+        .split('\0')
+        .filter(|path| !path.is_empty())
+        .map(|path| PathBuf::from(path.strip_prefix("./").unwrap_or(path)))
+        .collect();
+    let truncated = files.len() > MAX_FILES;
+    files.truncate(MAX_FILES);
+    Some(Impact::new(action, (files, truncated)))
+}
+
+/// Splits arguments into options and operands. Options listed in
+/// `with_value` take the next argument unless it is attached with `=`.
+fn split_args<'a>(
+    words: &'a [String],
+    with_value: &[&str],
+) -> (Vec<(&'a str, Option<&'a str>)>, Vec<&'a str>) {
+    let mut options = Vec::new();
+    let mut operands = Vec::new();
+    let mut only_operands = false;
+    let mut iter = words.iter();
+
+    while let Some(word) = iter.next() {
+        let text = word.as_str();
+        if only_operands || !text.starts_with('-') || text == "-" {
+            operands.push(text);
+        } else if text == "--" {
+            only_operands = true;
+        } else if let Some((name, value)) = text.split_once('=') {
+            options.push((name, Some(value)));
+        } else if with_value.contains(&text) {
+            options.push((text, iter.next().map(String::as_str)));
+        } else {
+            options.push((text, None));
+        }
+    }
+
+    (options, operands)
+}
+
+/// The operands that exist, plus everything beneath directories when the
+/// command recurses.
+fn existing(dir: &Path, operands: &[&str], recursive: bool) -> (Vec<PathBuf>, bool) {
+    let mut files = Vec::new();
+
+    for operand in operands {
+        let path = PathBuf::from(operand);
+        let Ok(metadata) = fs::symlink_metadata(dir.join(&path)) else {
+            continue;
+        };
+        files.push(path.clone());
+        if recursive && metadata.is_dir() {
+            let mut pending = vec![path];
+            while let Some(current) = pending.pop() {
+                let Ok(entries) = fs::read_dir(dir.join(&current)) else {
+                    continue;
+                };
+                for entry in entries.flatten() {
+                    let child = current.join(entry.file_name());
+                    if entry.file_type().is_ok_and(|kind| kind.is_dir()) {
+                        pending.push(child.clone());
+                    }
+                    files.push(child);
+                    if files.len() >= MAX_FILES {
+                        return (files, true);
+                    }
+                }
+            }
+        }
+        if files.len() >= MAX_FILES {
+            return (files, true);
+        }
+    }
+
+    (files, false)
+}
+
+/// Expands words the way the shell would before running the command, or
+/// returns `None` when that needs information only the shell has.
+fn expand_words(dir: &Path, words: &[Word]) -> Option<Vec<String>> {
+    let mut expanded = Vec::new();
+    for word in words {
+        expanded.extend(expand_word(dir, word)?);
+    }
+    Some(expanded)
+}
+
+fn expand_word(dir: &Path, word: &Word) -> Option<Vec<String>> {
+    if has_expansion(&word.parts) {
+        return None;
+    }
+    if word.is_quoted() {
+        // Partly quoted patterns like "my dir"/*.log cannot be told apart
+        // from literal characters once the quotes are gone.
+        if has_unquoted_pattern(word) {
+            return None;
+        }
+        return Some(vec![word.text.clone()]);
+    }
+
+    let text = match word.text.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => {
+            format!("{}{rest}", dirs::home_dir()?.display())
+        }
+        _ => word.text.clone(),
+    };
+
+    let mut expanded = Vec::new();
+    for text in expand_braces(&text)? {
+        if !text.contains(['*', '?', '[']) {
+            expanded.push(text);
+            continue;
+        }
+        let matches = glob_matches(dir, &text);
+        if matches.is_empty() {
+            // Like bash without `nullglob`, a pattern with no matches is
+            // passed through unchanged.
+            expanded.push(text);
+        } else {
+            expanded.extend(matches);
+        }
+    }
+    Some(expanded)
+}
+
+fn has_expansion(parts: &[WordPart]) -> bool {
+    parts.iter().any(|part| match part {
+        WordPart::Literal(_) | WordPart::SingleQuoted(_) | WordPart::Escaped(_) => false,
+        WordPart::DoubleQuoted(inner) => has_expansion(inner),
+        _ => true,
+    })
+}
+
+fn has_unquoted_pattern(word: &Word) -> bool {
+    word.parts.iter().any(
+        |part| matches!(part, WordPart::Literal(text) if text.contains(['*', '?', '[', '{', '~'])),
+    )
+}
+
+/// Bash-style `{a,b}` expansion. Braces without a comma stay literal, and
+/// `{1..5}` ranges are reported as unknown rather than guessed at.
+fn expand_braces(text: &str) -> Option<Vec<String>> {
+    for (open, _) in text.match_indices('{') {
+        let mut depth = 0;
+        let mut commas = Vec::new();
+        let mut close = None;
+        for (index, byte) in text.bytes().enumerate().skip(open) {
+            match byte {
+                b'{' => depth += 1,
+                b'}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        close = Some(index);
+                        break;
+                    }
+                }
+                b',' if depth == 1 => commas.push(index),
+                _ => {}
+            }
+        }
+
+        let Some(close) = close else {
+            continue;
+        };
+        if commas.is_empty() {
+            if text[open..close].contains("..") {
+                return None;
+            }
+            continue;
+        }
+
+        let (prefix, suffix) = (&text[..open], &text[close + 1..]);
+        let mut bounds = vec![open];
+        bounds.extend(commas);
+        bounds.push(close);
+
+        let mut expanded = Vec::new();
+        for pair in bounds.windows(2) {
+            let part = &text[pair[0] + 1..pair[1]];
+            expanded.extend(expand_braces(&format!("{prefix}{part}{suffix}"))?);
+        }
+        return Some(expanded);
+    }
+
+    Some(vec![text.to_string()])
+}
+
+fn glob_matches(dir: &Path, pattern: &str) -> Vec<String> {
+    let absolute = Path::new(pattern).is_absolute();
+    let full = if absolute {
+        pattern.to_string()
+    } else {
+        format!("{}/{pattern}", Pattern::escape(&dir.to_string_lossy()))
+    };
+    let options = MatchOptions {
+        case_sensitive: true,
+        require_literal_separator: true,
+        require_literal_leading_dot: true,
+    };
+    let Ok(paths) = glob::glob_with(&full, options) else {
+        return Vec::new();
+    };
+
+    paths
+        .flatten()
+        .map(|path| {
+            let shown = if absolute {
+                path.as_path()
+            } else {
+                path.strip_prefix(dir).unwrap_or(&path)
+            };
+            shown.to_string_lossy().into_owned()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn braces_expand_like_bash() {
+        assert_eq!(
+            expand_braces("a.{txt,log}").unwrap(),
+            vec!["a.txt", "a.log"]
+        );
+        assert_eq!(
+            expand_braces("{x,y}/{1,2}").unwrap(),
+            vec!["x/1", "x/2", "y/1", "y/2"]
+        );
+        assert_eq!(expand_braces("{solo}").unwrap(), vec!["{solo}"]);
+        assert_eq!(expand_braces("log.{1..5}"), None);
+    }
+
+    fn word(source: &str) -> Word {
+        shell::parse_partial(source).stages()[0].command.words[0].clone()
+    }
+
+    #[test]
+    fn quoting_decides_whether_patterns_expand() {
+        assert!(has_unquoted_pattern(&word(r#""my dir"/*.log"#)));
+        assert!(!has_unquoted_pattern(&word(r#""*.log""#)));
+        assert!(!has_unquoted_pattern(&word(r"\*.log")));
+    }
+
+    #[test]
+    fn expansions_are_found_inside_double_quotes() {
+        assert!(has_expansion(&word(r#""$dir"/a"#).parts));
+        assert!(has_expansion(&word("a$(pwd)").parts));
+        assert!(!has_expansion(&word("'$dir'").parts));
+        assert!(!has_expansion(&word(r"\$dir").parts));
+    }
+
+    #[test]
+    fn symbolic_modes_are_not_options() {
+        assert!(is_symbolic_mode("-wx"));
+        assert!(!is_symbolic_mode("-R"));
+        assert!(!is_symbolic_mode("-v"));
+    }
+}
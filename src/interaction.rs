@@ -1,7 +1,8 @@
 use crate::config::{ProviderKind, SystemPromptStyle};
 use crate::models;
+use crate::placeholder::{Placeholder, PlaceholderKind};
 use anyhow::{Context, Result};
-use dialoguer::{Confirm, Input, Select};
+use dialoguer::{Completion, Confirm, Editor, Input, Password, Select};
 
 pub fn select_provider(current: ProviderKind) -> Result<ProviderKind> {
     let providers = [
@@ -164,6 +165,197 @@ pub fn prompt_toggle(prompt: &str, default: bool) -> Result<bool> {
         .context("Failed to read option")
 }
 
+/// Shows the command pre-filled for editing: Enter runs whatever is on the
+/// line and clearing it cancels. Multi-line commands go through `$EDITOR`.
+pub fn edit_command(command: &str) -> Result<Option<String>> {
+    if command.contains('\n') {
+        return edit_multiline_command(command);
+    }
+
+    let edited: String = Input::new()
+        .with_prompt("Run (edit it, or clear the line to cancel)")
+        .with_initial_text(command)
+        .allow_empty(true)
+        .interact_text()
+        .context("Failed to read command")?;
+    Ok(non_empty(&edited))
+}
+
+fn edit_multiline_command(command: &str) -> Result<Option<String>> {
+    let choices = ["Run it", "Edit in $EDITOR first", "Cancel"];
+    let choice = Select::new()
+        .with_prompt("Run this multi-line command?")
+        .items(choices)
+        .default(0)
+        .interact()
+        .context("Failed to read confirmation")?;
+
+    match choice {
+        0 => Ok(Some(command.to_string())),
+        1 => {
+            let edited = Editor::new()
+                .extension(".sh")
+                .edit(command)
+                .context("Failed to open the editor")?;
+            Ok(edited.as_deref().and_then(non_empty))
+        }
+        _ => Ok(None),
+    }
+}
+
+fn non_empty(command: &str) -> Option<String> {
+    let trimmed = command.trim();
+    (!trimmed.is_empty()).then(|| trimmed.to_string())
+}
+
+pub fn confirm_high_risk(command: &str, action: &str) -> Result<bool> {
+    let answer: String = Input::new()
+        .with_prompt(format!(
+            "`{command}` is high risk. Type \"yes\" to {action} it anyway"
+        ))
+        .allow_empty(true)
+        .interact_text()
+        .context("Failed to read confirmation")?;
+    Ok(answer.trim().eq_ignore_ascii_case("yes"))
+}
+
+/// Asked on top of the usual confirmation for commands that use `tool`, with
+/// "no" as the default.
+pub fn confirm_escalation(tool: &str, action: &str) -> Result<bool> {
+    let mut prompt = format!("{action} it with {tool}?");
+    prompt[..1].make_ascii_uppercase();
+    Confirm::new()
+        .with_prompt(prompt)
+        .default(false)
+        .interact()
+        .context("Failed to read confirmation")
+}
+
+/// Lets the user pick one of several candidate commands; `None` if they
+/// dismiss the list with Esc or `q`.
+pub fn select_alternative(labels: &[String]) -> Result<Option<usize>> {
+    Select::new()
+        .with_prompt("Pick a command")
+        .items(labels)
+        .default(0)
+        .interact_opt()
+        .context("Failed to read command selection")
+}
+
+/// Asks for a placeholder's value. Suggestions are offered as a list first,
+/// and path prompts complete file names with Tab. Entering nothing keeps the
+/// placeholder as written.
+pub fn prompt_placeholder(placeholder: &Placeholder, suggestions: &[String]) -> Result<String> {
+    let token = &placeholder.token;
+    let prompt = format!("Value for {token}");
+
+    if !suggestions.is_empty() {
+        let mut items: Vec<String> = suggestions.iter().take(10).cloned().collect();
+        items.push("Type a value".to_string());
+        let choice = Select::new()
+            .with_prompt(&prompt)
+            .items(&items)
+            .default(0)
+            .interact()
+            .context("Failed to read placeholder value")?;
+        if choice + 1 < items.len() {
+            return Ok(items.swap_remove(choice));
+        }
+    }
+
+    let value = match placeholder.kind {
+        PlaceholderKind::Secret => Password::new()
+            .with_prompt(&prompt)
+            .allow_empty_password(true)
+            .interact()
+            .context("Failed to read placeholder value")?,
+        PlaceholderKind::Path => Input::new()
+            .with_prompt(&prompt)
+            .allow_empty(true)
+            .completion_with(&PathCompletion)
+            .interact_text()
+            .context("Failed to read placeholder value")?,
+        PlaceholderKind::Branch | PlaceholderKind::Text => Input::new()
+            .with_prompt(&prompt)
+            .allow_empty(true)
+            .interact_text()
+            .context("Failed to read placeholder value")?,
+    };
+
+    Ok(if value.is_empty() {
+        token.clone()
+    } else {
+        value
+    })
+}
+
+/// Completes the last path component against the current directory, up to
+/// the longest prefix the matching entries share.
+struct PathCompletion;
+
+impl Completion for PathCompletion {
+    fn get(&self, input: &str) -> Option<String> {
+        let (dir, prefix) = match input.rfind('/') {
+            Some(slash) => (&input[..=slash], &input[slash + 1..]),
+            None => ("", input),
+        };
+        let search = if dir.is_empty() { "." } else { dir };
+        let mut names: Vec<String> = std::fs::read_dir(search)
+            .ok()?
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                let visible = !name.starts_with('.') || prefix.starts_with('.');
+                (visible && name.starts_with(prefix)).then(|| {
+                    match entry.file_type().is_ok_and(|kind| kind.is_dir()) {
+                        true => format!("{name}/"),
+                        false => name,
+                    }
+                })
+            })
+            .collect();
+        names.sort();
+
+        let first = names.first()?;
+        let shared = names.iter().fold(first.len(), |len, name| {
+            first
+                .char_indices()
+                .zip(name.chars())
+                .take_while(|((_, a), b)| a == b)
+                .last()
+                .map_or(0, |((index, c), _)| index + c.len_utf8())
+                .min(len)
+        });
+        (shared > prefix.len()).then(|| format!("{dir}{}", &first[..shared]))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepAction {
+    Run,
+    Skip,
+    Edit,
+    Abort,
+}
+
+/// Asks what to do with the next step of a plan. Esc or `q` aborts.
+pub fn select_step_action() -> Result<StepAction> {
+    let choices = ["Run", "Skip", "Edit", "Abort"];
+    let choice = Select::new()
+        .with_prompt("Run this step?")
+        .items(choices)
+        .default(0)
+        .interact_opt()
+        .context("Failed to read step action")?;
+
+    Ok(match choice {
+        Some(0) => StepAction::Run,
+        Some(1) => StepAction::Skip,
+        Some(2) => StepAction::Edit,
+        _ => StepAction::Abort,
+    })
+}
+
 pub fn select_prompt_style(current: SystemPromptStyle) -> Result<SystemPromptStyle> {
     let styles = [
         (
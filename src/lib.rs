@@ -1,11 +1,26 @@
 #![cfg_attr(coverage, allow(unexpected_cfgs))]
 
 pub mod app;
+pub mod binaries;
 pub mod client;
+pub mod clipboard;
 pub mod commands;
 pub mod config;
+pub mod exec;
+pub mod extract;
+pub mod grounding;
+pub mod history;
+pub mod impact;
 pub mod interaction;
+pub mod lint;
 pub mod loader;
 pub mod models;
+pub mod placeholder;
+pub mod preview;
+pub mod privilege;
+pub mod risk;
+pub mod shell;
+pub mod snapshot;
+pub mod typo;
 pub mod ui;
 pub mod version;
@@ -0,0 +1,296 @@
+use crate::shell::{self, Command, Compound, Connector, List, Word, WordPart};
+use std::path::Path;
+
+/// Programs whose operands are file names, where a name starting with `-`
+/// would be read as an option.
+const PATH_PROGRAMS: &[&str] = &[
+    "cat", "chgrp", "chmod", "chown", "cp", "du", "head", "ln", "ls", "mkdir", "mv", "rm", "rmdir",
+    "tail", "touch", "wc",
+];
+
+/// Variables that always hold an absolute path, so they cannot start with `-`.
+const ABSOLUTE_VARIABLES: &[&str] = &["HOME", "PWD", "OLDPWD", "TMPDIR"];
+
+/// Special parameters that expand to a number or flags, never to spaces.
+const SAFE_PARAMETERS: &[&str] = &["$?", "$#", "$$", "$!", "$-"];
+
+/// A spaced path split into more words than this is not worth looking for.
+const MAX_SPLIT_WORDS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rule {
+    UnquotedVariable,
+    UnquotedSpacedPath,
+    ParsedLs,
+    MissingDoubleDash,
+    UncheckedCd,
+}
+
+/// A quoting or robustness problem in a generated command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub rule: Rule,
+    pub message: String,
+}
+
+pub fn lint(command: &str) -> Vec<Finding> {
+    let dir = std::env::current_dir().unwrap_or_default();
+    lint_in(&dir, command)
+}
+
+/// Checks `command` for unquoted expansions, spaced paths left bare, `ls`
+/// output read in loops, path operands without `--`, and `cd` whose failure
+/// goes unnoticed. Paths are looked up relative to `dir`.
+pub fn lint_in(dir: &Path, command: &str) -> Vec<Finding> {
+    let list = shell::parse_partial(command);
+    let stages = list.stages();
+    let mut findings = Vec::new();
+
+    if reads_ls_in_loop(&list) {
+        findings.push(Finding {
+            rule: Rule::ParsedLs,
+            message: "Parsing `ls` output breaks on names with spaces or newlines; loop over a glob such as `for f in ./*` instead".to_string(),
+        });
+    }
+
+    for (index, stage) in stages.iter().enumerate() {
+        let (_, args) = shell::unwrap_wrappers(&stage.command.words);
+        let program = args
+            .first()
+            .map(|word| shell::program_name(&word.text))
+            .unwrap_or_default();
+
+        for word in args {
+            if let Some(expansion) = unquoted_expansion(word) {
+                findings.push(Finding {
+                    rule: Rule::UnquotedVariable,
+                    message: format!(
+                        "`{expansion}` is unquoted, so spaces or globs in its value split it into several words; write \"{expansion}\""
+                    ),
+                });
+            }
+        }
+
+        findings.extend(spaced_paths(dir, args));
+
+        if PATH_PROGRAMS.contains(&program.as_str()) {
+            if let Some(operand) = operand_without_double_dash(args) {
+                findings.push(Finding {
+                    rule: Rule::MissingDoubleDash,
+                    message: format!(
+                        "`{operand}` could expand to a name starting with `-` that `{program}` reads as an option; put `--` before it"
+                    ),
+                });
+            }
+        }
+
+        if matches!(program.as_str(), "cd" | "pushd") {
+            let unchecked = stages
+                .get(index + 1)
+                .is_some_and(|next| !matches!(next.connector, Connector::And | Connector::Or));
+            if unchecked {
+                let written: Vec<&str> = args.iter().map(|word| word.raw.as_str()).collect();
+                findings.push(Finding {
+                    rule: Rule::UncheckedCd,
+                    message: format!(
+                        "If `{}` fails, the rest runs in the wrong directory; add `|| exit` or join the next command with `&&`",
+                        written.join(" ")
+                    ),
+                });
+            }
+        }
+    }
+
+    findings
+}
+
+/// The first expansion in `word` that undergoes word splitting, as written.
+/// Assignments such as `export OUT=$dir` do not split.
+fn unquoted_expansion(word: &Word) -> Option<String> {
+    if is_assignment(&word.raw) {
+        return None;
+    }
+    word.unquoted_expansions()
+        .into_iter()
+        .map(WordPart::text)
+        .find(|text| !SAFE_PARAMETERS.contains(&text.as_str()))
+}
+
+fn is_assignment(raw: &str) -> bool {
+    raw.split_once('=').is_some_and(|(name, _)| {
+        !name.is_empty()
+            && !name.starts_with(|c: char| c.is_ascii_digit())
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    })
+}
+
+/// Runs of bare words that only name an existing path when joined with spaces.
+fn spaced_paths(dir: &Path, args: &[Word]) -> Vec<Finding> {
+    let bare = |word: &Word| !word.is_quoted() && !word.raw.contains(['$', '`', '*', '?']);
+    let mut findings = Vec::new();
+    let mut index = 1;
+    while index < args.len() {
+        let longest = (2..=MAX_SPLIT_WORDS)
+            .rev()
+            .filter(|&count| index + count <= args.len())
+            .find(|&count| {
+                let run = &args[index..index + count];
+                if !run.iter().all(bare) {
+                    return false;
+                }
+                let joined: Vec<&str> = run.iter().map(|word| word.text.as_str()).collect();
+                dir.join(joined.join(" ")).exists()
+            });
+        match longest {
+            Some(count) => {
+                let joined: Vec<&str> = args[index..index + count]
+                    .iter()
+                    .map(|word| word.text.as_str())
+                    .collect();
+                let path = joined.join(" ");
+                findings.push(Finding {
+                    rule: Rule::UnquotedSpacedPath,
+                    message: format!(
+                        "`{path}` has spaces and is passed as {count} separate words; write '{path}'"
+                    ),
+                });
+                index += count;
+            }
+            None => index += 1,
+        }
+    }
+    findings
+}
+
+/// `for f in $(ls)`, `while read f; do …; done < <(ls)` or `ls | while read f`.
+fn reads_ls_in_loop(list: &List) -> bool {
+    let loops_over_ls = list.commands().into_iter().any(|command| {
+        let Command::Compound(compound) = command else {
+            return false;
+        };
+        match &compound.kind {
+            Compound::For { words, .. } | Compound::Select { words, .. } => words
+                .iter()
+                .flatten()
+                .flat_map(Word::substitutions)
+                .any(|substitution| runs_ls(&substitution.body)),
+            Compound::While { .. } | Compound::Until { .. } => compound
+                .redirects
+                .iter()
+                .flat_map(|redirect| redirect.target.substitutions())
+                .any(|substitution| runs_ls(&substitution.body)),
+            _ => false,
+        }
+    });
+
+    let pipes_ls_into_loop = list.pipelines().into_iter().any(|pipeline| {
+        pipeline.commands.windows(2).any(|pair| {
+            let is_ls = matches!(&pair[0], Command::Simple(simple) if simple
+                .program()
+                .is_some_and(|program| shell::program_name(program) == "ls"));
+            let is_loop = matches!(
+                &pair[1],
+                Command::Compound(compound) if matches!(
+                    compound.kind,
+                    Compound::While { .. } | Compound::Until { .. } | Compound::For { .. }
+                )
+            );
+            is_ls && is_loop
+        })
+    });
+
+    loops_over_ls || pipes_ls_into_loop
+}
+
+fn runs_ls(list: &List) -> bool {
+    list.stages().first().is_some_and(|stage| {
+        stage
+            .command
+            .program()
+            .is_some_and(|program| shell::program_name(program) == "ls")
+    })
+}
+
+/// The first operand that comes from a variable or a leading glob, when no
+/// `--` ends the options before it.
+fn operand_without_double_dash(args: &[Word]) -> Option<&str> {
+    for word in args.iter().skip(1) {
+        let raw = word.raw.as_str();
+        if raw == "--" {
+            return None;
+        }
+        if word.text.starts_with('-') && !word.is_quoted() {
+            continue;
+        }
+        let unquoted = raw.trim_start_matches('"');
+        let variable = unquoted
+            .strip_prefix('$')
+            .map(|rest| rest.trim_start_matches('{'))
+            .is_some_and(|rest| {
+                let end = rest
+                    .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                    .unwrap_or(rest.len());
+                !ABSOLUTE_VARIABLES.contains(&&rest[..end])
+            });
+        let glob = raw.starts_with(['*', '?']);
+        if variable || glob {
+            return Some(raw);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(command: &str) -> Vec<Word> {
+        shell::parse_partial(command).stages()[0]
+            .command
+            .words
+            .clone()
+    }
+
+    fn expansion(word: &str) -> Option<String> {
+        unquoted_expansion(&words(&format!("echo {word}"))[1])
+    }
+
+    #[test]
+    fn unquoted_expansions_are_found_outside_quotes() {
+        assert_eq!(expansion("$dir"), Some("$dir".to_string()));
+        assert_eq!(expansion("${dir}/out"), Some("${dir}".to_string()));
+        assert_eq!(expansion("$(pwd)/x"), Some("$(pwd)".to_string()));
+        assert_eq!(expansion("`date`"), Some("`date`".to_string()));
+        assert_eq!(expansion("\"$dir\""), None);
+        assert_eq!(expansion("'$dir'"), None);
+        assert_eq!(expansion("$?"), None);
+        assert_eq!(expansion("$((1 + 2))"), None);
+        assert_eq!(expansion("OUT=$dir"), None);
+    }
+
+    #[test]
+    fn double_dash_ends_the_check() {
+        assert_eq!(
+            operand_without_double_dash(&words("rm -f \"$file\"")),
+            Some("\"$file\"")
+        );
+        assert_eq!(operand_without_double_dash(&words("rm -- *.log")), None);
+        assert_eq!(
+            operand_without_double_dash(&words("cp \"$HOME/a\" b")),
+            None
+        );
+        assert_eq!(operand_without_double_dash(&words("rm ./*.log")), None);
+        assert_eq!(
+            operand_without_double_dash(&words("rm -r \"${PWD}\"/build")),
+            None
+        );
+        assert_eq!(
+            operand_without_double_dash(&words("rm -r \"$HOMEDIR/cache\"")),
+            Some("\"$HOMEDIR/cache\"")
+        );
+        assert_eq!(
+            operand_without_double_dash(&words("cp $PWDX b")),
+            Some("$PWDX")
+        );
+    }
+}
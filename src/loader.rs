@@ -11,6 +11,7 @@ const FRAME_DELAY: Duration = Duration::from_millis(90);
 
 pub struct ShimmerLoader {
     text: String,
+    start: Option<Instant>,
 }
 
 pub struct LoaderHandle {
@@ -20,16 +21,27 @@ pub struct LoaderHandle {
 
 impl ShimmerLoader {
     pub fn new(text: impl Into<String>) -> Self {
-        Self { text: text.into() }
+        Self {
+            text: text.into(),
+            start: None,
+        }
+    }
+
+    /// Counts the elapsed time from `start` instead of from when the loader
+    /// appears.
+    pub fn since(mut self, start: Instant) -> Self {
+        self.start = Some(start);
+        self
     }
 
     pub fn spawn(self) -> LoaderHandle {
         let notify = Arc::new(Notify::new());
         let notify_clone = Arc::clone(&notify);
         let text = self.text;
+        let start = self.start.unwrap_or_else(Instant::now);
 
         let join_handle = tokio::spawn(async move {
-            run_loader(text, notify_clone).await;
+            run_loader(text, start, notify_clone).await;
         });
 
         LoaderHandle {
@@ -41,7 +53,7 @@ impl ShimmerLoader {
 
 impl LoaderHandle {
     pub async fn stop(&mut self) {
-        self.notify.notify_waiters();
+        self.notify.notify_one();
 
         if let Some(handle) = self.join_handle.take() {
             let _ = handle.await;
@@ -51,7 +63,7 @@ impl LoaderHandle {
 
 impl Drop for LoaderHandle {
     fn drop(&mut self) {
-        self.notify.notify_waiters();
+        self.notify.notify_one();
 
         if let Some(handle) = self.join_handle.take() {
             handle.abort();
@@ -59,13 +71,12 @@ impl Drop for LoaderHandle {
     }
 }
 
-async fn run_loader(text: String, notify: Arc<Notify>) {
+async fn run_loader(text: String, start: Instant, notify: Arc<Notify>) {
     if text.is_empty() {
         return;
     }
 
     let _ = execute!(stdout(), cursor::Hide);
-    let start = Instant::now();
     let mut frame_index = 0;
 
     loop {
@@ -0,0 +1,403 @@
+use std::ops::Range;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaceholderKind {
+    Branch,
+    Path,
+    Secret,
+    Text,
+}
+
+/// A value the model left for the user to fill in, such as `<your-branch>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Placeholder {
+    /// Exactly as it appears in the command.
+    pub token: String,
+    pub kind: PlaceholderKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Quote {
+    None,
+    Single,
+    Double,
+}
+
+struct Occurrence {
+    range: Range<usize>,
+    /// Inside double quotes.
+    quoted: bool,
+}
+
+/// Where a token sat before filling and where its value sits after.
+struct Span {
+    token: Range<usize>,
+    value: Range<usize>,
+}
+
+/// Placeholders in `command`, each listed once in order of appearance.
+/// Recognizes `<name>`, `{{name}}`, `YOUR_API_KEY`-style names and
+/// `/path/to/...` example paths. Nothing inside single quotes counts, since
+/// that is where sed patterns, HTML and `--format '{{.Names}}'` live.
+pub fn find(command: &str) -> Vec<Placeholder> {
+    let mut placeholders: Vec<Placeholder> = Vec::new();
+    for occurrence in occurrences(command) {
+        let token = &command[occurrence.range];
+        if placeholders.iter().all(|seen| seen.token != token) {
+            placeholders.push(Placeholder {
+                token: token.to_string(),
+                kind: classify(token),
+            });
+        }
+    }
+    placeholders
+}
+
+/// Replaces every occurrence of each token with its value, quoted to suit
+/// where the token sits: bare or inside double quotes. A value equal to its
+/// token is left alone.
+pub fn fill(command: &str, values: &[(String, String)]) -> String {
+    fill_spans(command, values).0
+}
+
+/// [`fill`], also returning where each replaced token was and where its
+/// value went.
+fn fill_spans(command: &str, values: &[(String, String)]) -> (String, Vec<Span>) {
+    let mut filled = String::with_capacity(command.len());
+    let mut spans = Vec::new();
+    let mut copied = 0;
+    for occurrence in occurrences(command) {
+        let token = &command[occurrence.range.clone()];
+        let Some((_, value)) = values.iter().find(|(candidate, _)| candidate == token) else {
+            continue;
+        };
+        if value == token {
+            continue;
+        }
+        let replacement = if occurrence.quoted {
+            value
+                .chars()
+                .flat_map(|c| match c {
+                    '\\' | '"' | '$' | '`' => vec!['\\', c],
+                    _ => vec![c],
+                })
+                .collect()
+        } else {
+            shell_quote(value)
+        };
+        filled.push_str(&command[copied..occurrence.range.start]);
+        let start = filled.len();
+        filled.push_str(&replacement);
+        spans.push(Span {
+            token: occurrence.range.clone(),
+            value: start..filled.len(),
+        });
+        copied = occurrence.range.end;
+    }
+    filled.push_str(&command[copied..]);
+    (filled, spans)
+}
+
+/// A command with its placeholders filled in, keeping secret values apart.
+/// `shown` still has the secret placeholders as the model wrote them, and is
+/// what gets displayed, recorded in the history, or sent back to the provider.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Filled {
+    pub shown: String,
+    secrets: Vec<(String, String)>,
+}
+
+impl Filled {
+    /// Fills `values` into `command`, leaving the secret ones out of `shown`.
+    pub fn new(command: &str, values: &[(Placeholder, String)]) -> Self {
+        let (secrets, plain): (Vec<_>, Vec<_>) = values
+            .iter()
+            .partition(|(placeholder, _)| placeholder.kind == PlaceholderKind::Secret);
+        let tokens = |values: Vec<&(Placeholder, String)>| -> Vec<(String, String)> {
+            values
+                .into_iter()
+                .map(|(placeholder, value)| (placeholder.token.clone(), value.clone()))
+                .collect()
+        };
+        Self {
+            shown: fill(command, &tokens(plain)),
+            secrets: tokens(secrets),
+        }
+    }
+
+    /// A command with nothing to fill.
+    pub fn plain(command: &str) -> Self {
+        Self {
+            shown: command.to_string(),
+            secrets: Vec::new(),
+        }
+    }
+
+    /// The command to run, with the secrets in place.
+    pub fn command(&self) -> String {
+        fill(&self.shown, &self.secrets)
+    }
+
+    /// Puts the placeholders back where `text`, such as an error message
+    /// about the filled command, quotes a word a secret was filled into. Only
+    /// that whole word is matched, so a short value like `1` does not mask
+    /// unrelated text.
+    pub fn mask(&self, text: &str) -> String {
+        let (command, spans) = fill_spans(&self.shown, &self.secrets);
+        // Where an offset in `shown` lands in `command`, for offsets outside
+        // any replaced token.
+        let filled_offset = |offset: usize| -> usize {
+            spans
+                .iter()
+                .take_while(|span| span.token.end <= offset)
+                .fold(offset, |offset, span| {
+                    offset + span.value.len() - span.token.len()
+                })
+        };
+
+        let mut words: Vec<(&str, &str)> = spans
+            .iter()
+            .map(|Span { token, .. }| {
+                let start = self.shown[..token.start]
+                    .rfind(|c: char| c.is_ascii_whitespace())
+                    .map_or(0, |index| index + 1);
+                let end = self.shown[token.end..]
+                    .find(|c: char| c.is_ascii_whitespace())
+                    .map_or(self.shown.len(), |index| token.end + index);
+                (
+                    &command[filled_offset(start)..filled_offset(end)],
+                    &self.shown[start..end],
+                )
+            })
+            .collect();
+        words.sort_by_key(|(filled, _)| std::cmp::Reverse(filled.len()));
+
+        let mut masked = text.to_string();
+        for (filled, shown) in words {
+            masked = replace_word(&masked, filled, shown);
+        }
+        masked
+    }
+
+    /// The same secrets, filled into an edited version of `shown`.
+    pub fn edited(&self, shown: String) -> Self {
+        Self {
+            shown,
+            secrets: self.secrets.clone(),
+        }
+    }
+}
+
+/// Replaces each `word` in `text` that is not part of a longer word.
+fn replace_word(text: &str, word: &str, replacement: &str) -> String {
+    if word.is_empty() {
+        return text.to_string();
+    }
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let mut replaced = String::with_capacity(text.len());
+    let mut copied = 0;
+    for (index, _) in text.match_indices(word) {
+        let before = text[..index].chars().next_back();
+        let after = text[index + word.len()..].chars().next();
+        if index < copied || before.is_some_and(is_word) || after.is_some_and(is_word) {
+            continue;
+        }
+        replaced.push_str(&text[copied..index]);
+        replaced.push_str(replacement);
+        copied = index + word.len();
+    }
+    replaced.push_str(&text[copied..]);
+    replaced
+}
+
+/// Quotes `value` as a single shell word, leaving it bare when it is made of
+/// characters no shell treats specially.
+pub fn shell_quote(value: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "_@%+=:,./-".contains(c);
+    if !value.is_empty() && value.chars().all(safe) {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', r"'\''"))
+    }
+}
+
+/// Local branches, most recently committed first.
+pub fn local_branches(dir: &Path) -> Vec<String> {
+    let output = Command::new("git")
+        .args([
+            "for-each-ref",
+            "--sort=-committerdate",
+            "--format=%(refname:short)",
+            "refs/heads",
+        ])
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output();
+    match output {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Files in `dir` that look like what an example path stands for: those with
+/// the same extension when it has one.
+pub fn matching_files(dir: &Path, token: &str) -> Vec<String> {
+    let Some(extension) = Path::new(token).extension() else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut files: Vec<String> = entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_file()))
+        .filter(|entry| Path::new(&entry.file_name()).extension() == Some(extension))
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| !name.starts_with('.'))
+        .collect();
+    files.sort();
+    files
+}
+
+fn classify(token: &str) -> PlaceholderKind {
+    let lower = token.to_ascii_lowercase();
+    let has = |words: &[&str]| words.iter().any(|word| lower.contains(word));
+    if has(&["branch"]) {
+        PlaceholderKind::Branch
+    } else if has(&["key", "token", "secret", "password", "passwd"]) {
+        PlaceholderKind::Secret
+    } else if has(&["path/to/", "path", "file", "dir", "folder"]) {
+        PlaceholderKind::Path
+    } else {
+        PlaceholderKind::Text
+    }
+}
+
+fn occurrences(command: &str) -> Vec<Occurrence> {
+    let bytes = command.as_bytes();
+    let mut found = Vec::new();
+    let mut quote = Quote::None;
+    let mut index = 0;
+
+    while index < bytes.len() {
+        let byte = bytes[index];
+        match (quote, byte) {
+            (Quote::None, b'\'') => quote = Quote::Single,
+            (Quote::Single, b'\'') | (Quote::Double, b'"') => quote = Quote::None,
+            (Quote::None, b'"') => quote = Quote::Double,
+            (Quote::None | Quote::Double, b'\\') => index += 1,
+            (Quote::None | Quote::Double, _) if command.is_char_boundary(index) => {
+                let previous = index.checked_sub(1).map(|i| bytes[i]);
+                let quoted = quote == Quote::Double;
+                if let Some(end) = placeholder_end(command, index, previous, quoted) {
+                    found.push(Occurrence {
+                        range: index..end,
+                        quoted,
+                    });
+                    index = end;
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        index += 1;
+    }
+
+    found
+}
+
+/// Where a placeholder starting at `start` ends, if one does. `<name>` is
+/// only a placeholder outside quotes, where it could not be HTML.
+fn placeholder_end(
+    command: &str,
+    start: usize,
+    previous: Option<u8>,
+    quoted: bool,
+) -> Option<usize> {
+    let rest = &command[start..];
+    let is_name = |c: char| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.');
+    let at_boundary = previous.is_none_or(|c| !c.is_ascii_alphanumeric() && c != b'_');
+
+    if let Some(inner) = rest.strip_prefix("{{") {
+        let close = inner.find("}}")?;
+        let name = &inner[..close];
+        // `{{.Names}}` and `{{ .Values.x }}` are Go and Helm templates.
+        return (!name.is_empty() && !name.starts_with('.') && name.chars().all(is_name))
+            .then_some(start + 2 + close + 2);
+    }
+
+    if let Some(inner) = rest.strip_prefix('<') {
+        // `<<EOF` and `2<file` are redirections, not placeholders.
+        if quoted || !at_boundary || previous == Some(b'<') {
+            return None;
+        }
+        let name_len = inner.find(|c: char| !is_name(c)).unwrap_or(inner.len());
+        let name = &inner[..name_len];
+        let closes = inner[name_len..].starts_with('>');
+        return (closes && name.starts_with(|c: char| c.is_ascii_alphabetic()))
+            .then_some(start + 1 + name_len + 1);
+    }
+
+    if !at_boundary || previous == Some(b'$') || previous == Some(b'/') {
+        return None;
+    }
+
+    for prefix in ["/path/to/", "path/to/", "~/path/to/"] {
+        if rest.starts_with(prefix) {
+            let len = rest
+                .find(|c: char| c.is_whitespace() || "'\"`;|&<>()".contains(c))
+                .unwrap_or(rest.len());
+            return Some(start + len);
+        }
+    }
+
+    let len = rest
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+        .unwrap_or(rest.len());
+    let word = &rest[..len];
+    let lower = word.to_ascii_lowercase();
+    let shouty = word.chars().any(|c| c.is_ascii_uppercase())
+        && !word.chars().any(|c| c.is_ascii_lowercase());
+    if rest[len..].starts_with('=') {
+        // `MY_VAR=1` assigns a variable.
+        return None;
+    }
+    let is_placeholder = ((lower.starts_with("your_") || lower.starts_with("your-")) && len > 5)
+        || (shouty && (word.starts_with("MY_") || word.ends_with("_HERE")));
+    is_placeholder.then_some(start + len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classification_follows_the_name() {
+        assert_eq!(classify("<your-branch>"), PlaceholderKind::Branch);
+        assert_eq!(classify("YOUR_API_KEY"), PlaceholderKind::Secret);
+        assert_eq!(classify("/path/to/file"), PlaceholderKind::Path);
+        assert_eq!(classify("{{name}}"), PlaceholderKind::Text);
+    }
+
+    #[test]
+    fn occurrences_track_the_quote_context() {
+        let command = r#"echo YOUR_NAME "YOUR_NAME" 'YOUR_NAME'"#;
+        let quoted: Vec<bool> = occurrences(command)
+            .iter()
+            .map(|occurrence| occurrence.quoted)
+            .collect();
+        assert_eq!(quoted, vec![false, true]);
+    }
+
+    #[test]
+    fn only_whole_words_are_replaced() {
+        assert_eq!(replace_word("a 1, x1 1b (1)", "1", "N"), "a N, x1 1b (N)");
+    }
+}
@@ -0,0 +1,268 @@
+use anyhow::Result;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PreviewReport {
+    pub created: Vec<PathBuf>,
+    pub modified: Vec<PathBuf>,
+    pub deleted: Vec<PathBuf>,
+    /// Exit status of the command inside the sandbox.
+    pub exit_code: Option<i32>,
+}
+
+impl PreviewReport {
+    pub fn is_empty(&self) -> bool {
+        self.created.is_empty() && self.modified.is_empty() && self.deleted.is_empty()
+    }
+}
+
+/// Runs `command` against a throwaway copy-on-write view of the current
+/// directory and reports what it changed. The real tree is never touched.
+pub async fn preview(command: &str) -> Result<PreviewReport> {
+    let dir = std::env::current_dir()?;
+    preview_in(&dir, command).await
+}
+
+#[cfg(not(target_os = "linux"))]
+pub async fn preview_in(_dir: &Path, _command: &str) -> Result<PreviewReport> {
+    anyhow::bail!("Previews need Linux user and mount namespaces")
+}
+
+#[cfg(target_os = "linux")]
+pub async fn preview_in(dir: &Path, command: &str) -> Result<PreviewReport> {
+    sandbox::run(dir, command).await
+}
+
+/// Compares the tree before and after the command. `rewritten` holds the
+/// files the overlay copied up, which covers edits that keep a file's name.
+/// Inside a created or deleted directory only the directory itself is listed.
+pub fn summarize(
+    before: &BTreeSet<PathBuf>,
+    after: &BTreeSet<PathBuf>,
+    rewritten: &BTreeSet<PathBuf>,
+) -> PreviewReport {
+    PreviewReport {
+        created: outermost(after.difference(before)),
+        modified: rewritten
+            .iter()
+            .filter(|path| before.contains(*path) && after.contains(*path))
+            .cloned()
+            .collect(),
+        deleted: outermost(before.difference(after)),
+        exit_code: None,
+    }
+}
+
+fn outermost<'a>(paths: impl Iterator<Item = &'a PathBuf>) -> Vec<PathBuf> {
+    let mut kept: Vec<PathBuf> = Vec::new();
+    for path in paths {
+        if kept.last().is_some_and(|parent| path.starts_with(parent)) {
+            continue;
+        }
+        kept.push(path.clone());
+    }
+    kept
+}
+
+#[cfg(target_os = "linux")]
+mod sandbox {
+    use super::{PreviewReport, summarize};
+    use crate::exec;
+    use anyhow::{Context, Result, anyhow};
+    use std::collections::BTreeSet;
+    use std::ffi::OsStr;
+    use std::fs;
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::fs::MetadataExt;
+    use std::path::{Path, PathBuf};
+    use std::process::Stdio;
+    use std::time::Duration;
+    use tempfile::TempDir;
+    use tokio::process::Command;
+
+    const PREVIEW_TIMEOUT: Duration = Duration::from_secs(30);
+    /// Room for the files the command writes, held in memory.
+    const SCRATCH_SIZE: &str = "256m";
+    /// Trees bigger than this are too slow to diff, so they are not previewed.
+    const MAX_ENTRIES: usize = 100_000;
+    /// Point clients at sockets outside the sandbox, which a read-only mount
+    /// does not stop them from connecting to.
+    const SOCKET_VARIABLES: &[&str] = &["SSH_AUTH_SOCK", "DBUS_SESSION_BUS_ADDRESS"];
+
+    /// Runs as root of fresh user, mount, pid and network namespaces: mounts
+    /// a size-capped tmpfs for the overlay on the working directory, makes
+    /// every other mount read-only, hides the sockets under the runtime
+    /// directories, and runs the command. It then prints the exit status, the
+    /// resulting tree, an empty field, and the files the overlay copied up,
+    /// all NUL-separated. A mount that stays writable aborts the preview.
+    const SANDBOX_SCRIPT: &str = r#"set -e
+cwd=$PWD
+scratch=$1
+mount -t tmpfs -o "size=$2,mode=700" tmpfs "$scratch"
+mkdir "$scratch/upper" "$scratch/work"
+mount -t overlay overlay -o "lowerdir=$cwd,upperdir=$scratch/upper,workdir=$scratch/work" "$cwd"
+while read -r _ point _; do
+  point=$(printf '%b' "$point")
+  case $point in "$cwd" | "$scratch") continue ;; esac
+  if ! mount -o remount,bind,ro "$point" 2>/dev/null; then
+    echo "could not make $point read-only" >&2
+    exit 1
+  fi
+done </proc/self/mounts
+for runtime in "$XDG_RUNTIME_DIR" /var/run /run; do
+  if [ -n "$runtime" ] && [ -d "$runtime" ]; then
+    mount -t tmpfs -o size=1m tmpfs "$runtime"
+  fi
+done
+cd "$cwd"
+set +e
+"$3" -c "$4" </dev/null >/dev/null 2>&1
+printf '%s\0' "$?"
+find . -xdev -print0
+printf '\0'
+cd "$scratch/upper" && find . ! -type d ! -type c -print0
+"#;
+
+    pub(super) async fn run(dir: &Path, command: &str) -> Result<PreviewReport> {
+        let dir_text = dir.to_string_lossy();
+        if dir_text.contains([',', ':', '\\']) {
+            anyhow::bail!(
+                "Cannot preview in {dir_text}: overlay mounts reject ',' and ':' in paths"
+            );
+        }
+
+        let scratch = scratch_dir(dir)?;
+        let before = list_tree(dir)?;
+        let (shell, _) = exec::resolve_shell();
+
+        let mut sandbox = Command::new("unshare");
+        sandbox
+            .args([
+                "--user",
+                "--map-root-user",
+                "--mount",
+                "--pid",
+                "--net",
+                "--fork",
+                "--kill-child",
+                "--",
+                "sh",
+                "-c",
+                SANDBOX_SCRIPT,
+                "sh",
+            ])
+            .arg(scratch.path())
+            .arg(SCRATCH_SIZE)
+            .arg(&shell)
+            .arg(command)
+            .current_dir(dir)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        for name in SOCKET_VARIABLES {
+            sandbox.env_remove(name);
+        }
+        let output = tokio::time::timeout(PREVIEW_TIMEOUT, sandbox.output())
+            .await
+            .map_err(|_| {
+                anyhow!(
+                    "the command was still running after {}s",
+                    PREVIEW_TIMEOUT.as_secs()
+                )
+            })?
+            .context("Failed to start unshare")?;
+
+        let mut fields = output.stdout.split(|byte| *byte == 0);
+        let exit_code = fields
+            .next()
+            .and_then(|status| std::str::from_utf8(status).ok()?.parse().ok());
+        let Some(exit_code) = exit_code else {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let reason = stderr.lines().next().unwrap_or("unknown error").trim();
+            anyhow::bail!("Could not set up the sandbox: {reason}");
+        };
+
+        let after = read_paths(fields.by_ref().take_while(|raw| !raw.is_empty()))?;
+        let rewritten = read_paths(fields)?;
+
+        let mut report = summarize(&before, &after, &rewritten);
+        report.exit_code = Some(exit_code);
+        Ok(report)
+    }
+
+    /// Relative paths from `find . -print0`, without `.` itself.
+    fn read_paths<'a>(fields: impl Iterator<Item = &'a [u8]>) -> Result<BTreeSet<PathBuf>> {
+        let mut paths = BTreeSet::new();
+        for raw in fields.filter(|raw| !raw.is_empty() && *raw != b".") {
+            let path = Path::new(OsStr::from_bytes(raw));
+            paths.insert(path.strip_prefix(".").unwrap_or(path).to_path_buf());
+            if paths.len() > MAX_ENTRIES {
+                anyhow::bail!("the command left more than {MAX_ENTRIES} files to compare");
+            }
+        }
+        Ok(paths)
+    }
+
+    /// An empty directory for the sandbox to mount its scratch tmpfs on. It
+    /// must not sit inside the directory being previewed.
+    fn scratch_dir(dir: &Path) -> Result<TempDir> {
+        let shm = Path::new("/dev/shm");
+        let candidates = [shm.to_path_buf(), std::env::temp_dir()];
+
+        for base in candidates {
+            if !base.is_dir() || base.starts_with(dir) {
+                continue;
+            }
+            if let Ok(scratch) = tempfile::Builder::new()
+                .prefix("tt-preview")
+                .tempdir_in(&base)
+            {
+                return Ok(scratch);
+            }
+        }
+
+        anyhow::bail!(
+            "Could not find a scratch directory outside {}",
+            dir.display()
+        )
+    }
+
+    /// Every path under `root`, relative to it. Directories on other
+    /// filesystems are listed but not entered, matching `find -xdev`. Fails
+    /// past [`MAX_ENTRIES`] rather than diffing part of the tree.
+    fn list_tree(root: &Path) -> Result<BTreeSet<PathBuf>> {
+        let device = fs::metadata(root)
+            .with_context(|| format!("Could not read {}", root.display()))?
+            .dev();
+        let mut paths = BTreeSet::new();
+        let mut pending = vec![root.to_path_buf()];
+
+        while let Some(dir) = pending.pop() {
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let Ok(metadata) = fs::symlink_metadata(&path) else {
+                    continue;
+                };
+                if metadata.is_dir() && metadata.dev() == device {
+                    pending.push(path.clone());
+                }
+                if let Ok(relative) = path.strip_prefix(root) {
+                    paths.insert(relative.to_path_buf());
+                }
+                if paths.len() > MAX_ENTRIES {
+                    anyhow::bail!(
+                        "{} holds more than {MAX_ENTRIES} files to compare",
+                        root.display()
+                    );
+                }
+            }
+        }
+
+        Ok(paths)
+    }
+}
@@ -0,0 +1,221 @@
+use crate::binaries;
+use crate::shell::{self, List, MAX_NESTING};
+use std::fmt;
+use tokio::sync::OnceCell;
+
+/// Programs that run a command as another user, in order of preference.
+const ESCALATION_TOOLS: &[&str] = &["sudo", "doas"];
+/// Groups that are granted sudo or doas by the stock configuration on common
+/// distributions and macOS.
+const ADMIN_GROUPS: &[&str] = &["sudo", "wheel", "admin"];
+#[cfg(unix)]
+const PROBE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// What the current user can do about commands that need root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Privilege {
+    Root,
+    /// `tool -n true` succeeded, so the tool works without a password prompt.
+    Passwordless {
+        tool: String,
+    },
+    /// In an admin group, so the tool should work after a password prompt.
+    AdminGroup {
+        tool: String,
+        group: String,
+    },
+    /// No sign of escalation rights; `tool` is installed but will likely refuse.
+    Unprivileged {
+        tool: Option<String>,
+    },
+}
+
+impl Privilege {
+    /// Works out a non-root user's privilege from the installed escalation
+    /// tool, whether it ran without a password, and the user's groups.
+    pub fn classify(tool: Option<&str>, passwordless: bool, groups: &[String]) -> Self {
+        let Some(tool) = tool else {
+            return Privilege::Unprivileged { tool: None };
+        };
+        if passwordless {
+            return Privilege::Passwordless {
+                tool: tool.to_string(),
+            };
+        }
+        match groups
+            .iter()
+            .find(|group| ADMIN_GROUPS.contains(&group.as_str()))
+        {
+            Some(group) => Privilege::AdminGroup {
+                tool: tool.to_string(),
+                group: group.clone(),
+            },
+            None => Privilege::Unprivileged {
+                tool: Some(tool.to_string()),
+            },
+        }
+    }
+
+    pub fn can_escalate(&self) -> bool {
+        !matches!(self, Privilege::Unprivileged { .. })
+    }
+}
+
+impl fmt::Display for Privilege {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Privilege::Root => {
+                write!(
+                    f,
+                    "running as root; do not prefix commands with sudo or doas"
+                )
+            }
+            Privilege::Passwordless { tool } => write!(
+                f,
+                "not root; `{tool}` works without a password. Use it only for commands that need root"
+            ),
+            Privilege::AdminGroup { tool, group } => write!(
+                f,
+                "not root; in the `{group}` group, so `{tool}` works after a password prompt. Use it only for commands that need root"
+            ),
+            Privilege::Unprivileged { tool: Some(tool) } => write!(
+                f,
+                "not root and cannot use `{tool}`; avoid it and prefer user-level alternatives (`--user` installs, paths under $HOME)"
+            ),
+            Privilege::Unprivileged { tool: None } => write!(
+                f,
+                "not root and neither sudo nor doas is installed; prefer user-level alternatives (`--user` installs, paths under $HOME)"
+            ),
+        }
+    }
+}
+
+/// The current user's privilege, worked out once per process on a blocking
+/// thread. `None` on platforms without sudo. Root and admin groups are always
+/// checked; `probe_sudo` also runs `sudo -n true`, which sudo may log, so
+/// only pass it when a command will run.
+pub async fn current(probe_sudo: bool) -> Option<&'static Privilege> {
+    static PROBED: OnceCell<Option<Privilege>> = OnceCell::const_new();
+    static UNPROBED: OnceCell<Option<Privilege>> = OnceCell::const_new();
+
+    let (cell, probe_sudo) = match PROBED.get() {
+        Some(_) => (&PROBED, true),
+        None if probe_sudo => (&PROBED, true),
+        None => (&UNPROBED, false),
+    };
+    cell.get_or_init(|| async move {
+        tokio::task::spawn_blocking(move || detect(probe_sudo))
+            .await
+            .ok()
+            .flatten()
+    })
+    .await
+    .as_ref()
+}
+
+#[cfg(unix)]
+fn detect(probe_sudo: bool) -> Option<Privilege> {
+    // SAFETY: geteuid has no preconditions and cannot fail.
+    let is_root = unsafe { libc::geteuid() } == 0;
+    if is_root {
+        return Some(Privilege::Root);
+    }
+
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let tool = ESCALATION_TOOLS
+        .iter()
+        .copied()
+        .find(|tool| binaries::is_on_path(tool, &path));
+    let passwordless = probe_sudo && tool.is_some_and(|tool| probe(tool, &["-n", "true"]));
+    let groups = std::process::Command::new("id")
+        .arg("-Gn")
+        .output()
+        .map(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .split_whitespace()
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    Some(Privilege::classify(tool, passwordless, &groups))
+}
+
+#[cfg(not(unix))]
+fn detect(_probe_sudo: bool) -> Option<Privilege> {
+    None
+}
+
+/// Whether `program` exits successfully within [`PROBE_TIMEOUT`]. `-n` keeps
+/// sudo from prompting, but a slow directory lookup could still stall it.
+#[cfg(unix)]
+fn probe(program: &str, args: &[&str]) -> bool {
+    use std::process::{Command, Stdio};
+    use std::time::Instant;
+
+    let Ok(mut child) = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+    else {
+        return false;
+    };
+    let started = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => return status.success(),
+            Ok(None) if started.elapsed() < PROBE_TIMEOUT => {
+                std::thread::sleep(std::time::Duration::from_millis(20));
+            }
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return false;
+            }
+        }
+    }
+}
+
+/// A part of a command that runs through `sudo` or `doas`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Escalation {
+    pub tool: String,
+    /// What the tool runs, or the whole stage when it runs nothing (`sudo -v`).
+    pub command: String,
+}
+
+/// Every stage of `command` that escalates, including those inside
+/// command substitutions.
+pub fn escalations(command: &str) -> Vec<Escalation> {
+    let mut found = Vec::new();
+    collect_escalations(&shell::parse_partial(command), 0, &mut found);
+    found
+}
+
+fn collect_escalations(list: &List, depth: usize, found: &mut Vec<Escalation>) {
+    if depth > MAX_NESTING {
+        return;
+    }
+
+    for stage in list.stages() {
+        let words = &stage.command.words;
+        let (wrappers, args) = shell::unwrap_wrappers(words);
+        let Some(tool) = wrappers
+            .into_iter()
+            .find(|wrapper| ESCALATION_TOOLS.contains(&wrapper.as_str()))
+        else {
+            continue;
+        };
+        let shown = if args.is_empty() { &words[..] } else { args };
+        let command: Vec<&str> = shown.iter().map(|word| word.raw.as_str()).collect();
+        found.push(Escalation {
+            tool,
+            command: command.join(" "),
+        });
+    }
+    for substitution in list.substitutions() {
+        collect_escalations(&substitution.body, depth + 1, found);
+    }
+}
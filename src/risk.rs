@@ -0,0 +1,393 @@
+use crate::shell::{self, Connector, List, MAX_NESTING, Word, program_name};
+
+const SHELL_INTERPRETERS: &[&str] = &[
+    "sh", "bash", "zsh", "dash", "ksh", "fish", "python", "python3", "perl", "ruby", "node",
+];
+const DOWNLOADERS: &[&str] = &["curl", "wget", "fetch", "iwr", "invoke-webrequest"];
+const DISK_TOOLS: &[&str] = &[
+    "mkswap", "wipefs", "fdisk", "sfdisk", "gdisk", "parted", "shred", "diskutil",
+];
+/// Options git itself takes before the subcommand, with their value as the
+/// next word.
+const GIT_VALUE_OPTIONS: &[&str] = &[
+    "-C",
+    "-c",
+    "--git-dir",
+    "--work-tree",
+    "--namespace",
+    "--config-env",
+];
+const SYSTEM_PATHS: &[&str] = &[
+    "/etc",
+    "/usr",
+    "/bin",
+    "/sbin",
+    "/lib",
+    "/lib32",
+    "/lib64",
+    "/boot",
+    "/dev",
+    "/sys",
+    "/proc",
+    "/var/lib",
+    "/System",
+    "/Library",
+    "/private/etc",
+];
+const HARMLESS_DEVICES: &[&str] = &[
+    "/dev/null",
+    "/dev/zero",
+    "/dev/stdout",
+    "/dev/stderr",
+    "/dev/stdin",
+    "/dev/tty",
+    "/dev/random",
+    "/dev/urandom",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum RiskLevel {
+    #[default]
+    Low,
+    Medium,
+    High,
+}
+
+impl RiskLevel {
+    pub fn label(&self) -> &'static str {
+        match self {
+            RiskLevel::Low => "LOW RISK",
+            RiskLevel::Medium => "MEDIUM RISK",
+            RiskLevel::High => "HIGH RISK",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RiskFinding {
+    pub level: RiskLevel,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RiskReport {
+    pub findings: Vec<RiskFinding>,
+}
+
+impl RiskReport {
+    pub fn level(&self) -> RiskLevel {
+        self.findings
+            .iter()
+            .map(|finding| finding.level)
+            .max()
+            .unwrap_or_default()
+    }
+
+    fn flag(&mut self, level: RiskLevel, reason: impl Into<String>) {
+        let reason = reason.into();
+        if !self.findings.iter().any(|finding| finding.reason == reason) {
+            self.findings.push(RiskFinding { level, reason });
+        }
+    }
+}
+
+/// Scores a generated command without running it. The checks are heuristics
+/// over the command's structure, so they err on the side of flagging.
+pub fn assess(command: &str) -> RiskReport {
+    let mut report = RiskReport::default();
+
+    let compact: String = command.chars().filter(|c| !c.is_whitespace()).collect();
+    if compact.contains(":(){:|:&};:") {
+        report.flag(RiskLevel::High, "fork bomb");
+    }
+
+    assess_source(command, 0, &mut report);
+    report
+}
+
+fn assess_source(source: &str, depth: usize, report: &mut RiskReport) {
+    assess_list(&shell::parse_partial(source), depth, report);
+}
+
+fn assess_list(list: &List, depth: usize, report: &mut RiskReport) {
+    if depth > MAX_NESTING {
+        return;
+    }
+
+    for substitution in list.substitutions() {
+        assess_list(&substitution.body, depth + 1, report);
+    }
+    check_redirects(list, report);
+
+    let mut previous_program: Option<String> = None;
+    for stage in list.stages() {
+        let command = stage.command;
+        let (wrappers, args) = shell::unwrap_wrappers(&command.words);
+        for wrapper in wrappers {
+            if matches!(wrapper.as_str(), "sudo" | "doas") {
+                report.flag(
+                    RiskLevel::Medium,
+                    format!("runs with elevated privileges ({wrapper})"),
+                );
+            }
+        }
+        let program = args.first().map(|word| program_name(&word.text));
+
+        if let (Some(program), Some(previous)) = (&program, &previous_program) {
+            if stage.connector == Connector::Pipe
+                && DOWNLOADERS.contains(&previous.as_str())
+                && SHELL_INTERPRETERS.contains(&program.as_str())
+            {
+                report.flag(
+                    RiskLevel::High,
+                    format!("pipes a download straight into {program}"),
+                );
+            }
+        }
+
+        if let Some(program) = &program {
+            if SHELL_INTERPRETERS.contains(&program.as_str())
+                && command
+                    .substitutions()
+                    .iter()
+                    .any(|inner| mentions_downloader(&inner.body))
+            {
+                report.flag(
+                    RiskLevel::High,
+                    format!("runs a downloaded script with {program}"),
+                );
+            }
+            assess_program(program, &args[1..], depth, report);
+        }
+
+        previous_program = program;
+    }
+}
+
+fn mentions_downloader(list: &List) -> bool {
+    list.stages().iter().any(|stage| {
+        stage
+            .command
+            .program()
+            .is_some_and(|program| DOWNLOADERS.contains(&program_name(program).as_str()))
+    })
+}
+
+fn check_redirects(list: &List, report: &mut RiskReport) {
+    for redirect in list.redirects() {
+        if redirect.op.writes() && is_system_path(&redirect.target.text) {
+            report.flag(
+                RiskLevel::High,
+                format!("writes to system path {}", redirect.target.text),
+            );
+        }
+    }
+}
+
+fn assess_program(program: &str, args: &[Word], depth: usize, report: &mut RiskReport) {
+    let (flags, operands) = partition_args(args);
+
+    match program {
+        "rm" => {
+            let recursive = has_short_flag(&flags, 'r')
+                || has_short_flag(&flags, 'R')
+                || flags.contains(&"--recursive");
+            let dangerous_target = operands.iter().find(|target| is_sweeping_target(target));
+
+            match (recursive, dangerous_target) {
+                (_, Some(target)) => report.flag(RiskLevel::High, format!("deletes {target}")),
+                (true, None) => report.flag(RiskLevel::High, "recursive delete (rm -r)"),
+                (false, None) => report.flag(RiskLevel::Medium, "deletes files"),
+            }
+        }
+        "dd" => match operands.iter().find_map(|arg| arg.strip_prefix("of=")) {
+            Some(target) => report.flag(RiskLevel::High, format!("dd writes raw data to {target}")),
+            None => report.flag(RiskLevel::Medium, "raw disk copy (dd)"),
+        },
+        "chmod" | "chown" | "chgrp" => {
+            let recursive = has_short_flag(&flags, 'R') || flags.contains(&"--recursive");
+            let world_writable = program == "chmod"
+                && operands
+                    .first()
+                    .is_some_and(|mode| mode.ends_with("777") || mode.contains("o+w"));
+            let system_target = operands
+                .iter()
+                .skip(1)
+                .any(|target| is_sweeping_target(target));
+
+            if system_target || (recursive && world_writable) {
+                report.flag(
+                    RiskLevel::High,
+                    format!("{program} {} on a system-wide target", operands.join(" ")),
+                );
+            } else if recursive {
+                report.flag(RiskLevel::Medium, format!("recursive {program}"));
+            } else if world_writable {
+                report.flag(RiskLevel::Medium, "makes files world-writable");
+            }
+        }
+        "git" => {
+            let (flags, operands) = partition_args(git_subcommand(args));
+            assess_git(&flags, &operands, report);
+        }
+        "find" => {
+            if flags.contains(&"-delete") {
+                report.flag(RiskLevel::Medium, "deletes matched files (find -delete)");
+            }
+            if let Some(start) = args
+                .iter()
+                .position(|word| matches!(word.text.as_str(), "-exec" | "-execdir" | "-ok"))
+            {
+                let inner: Vec<&str> = args[start + 1..]
+                    .iter()
+                    .map(|word| word.text.as_str())
+                    .take_while(|text| *text != ";" && *text != "+")
+                    .collect();
+                assess_source(&inner.join(" "), depth + 1, report);
+            }
+        }
+        "mv" | "cp" | "tee" | "ln" | "install" | "truncate" | "touch" | "rsync" => {
+            let targets: Vec<&&str> = match program {
+                "cp" | "ln" | "install" | "rsync" => operands.last().into_iter().collect(),
+                _ => operands.iter().collect(),
+            };
+            if let Some(target) = targets.into_iter().find(|target| is_system_path(target)) {
+                report.flag(RiskLevel::High, format!("writes to system path {target}"));
+            }
+        }
+        "sed" | "perl" if flags.iter().any(|flag| flag.starts_with("-i")) => {
+            if let Some(target) = operands.iter().find(|target| is_system_path(target)) {
+                report.flag(RiskLevel::High, format!("edits {target} in place"));
+            }
+        }
+        "shutdown" | "reboot" | "halt" | "poweroff" => {
+            report.flag(RiskLevel::Medium, "shuts down or reboots the machine");
+        }
+        _ if program.starts_with("mkfs") || program == "mke2fs" => {
+            report.flag(RiskLevel::High, format!("formats a filesystem ({program})"));
+        }
+        _ if DISK_TOOLS.contains(&program) => {
+            report.flag(
+                RiskLevel::High,
+                format!("rewrites disks or partitions ({program})"),
+            );
+        }
+        _ if SHELL_INTERPRETERS.contains(&program) => {
+            if let Some(index) = args.iter().position(|word| word.text == "-c") {
+                if let Some(script) = args.get(index + 1) {
+                    assess_source(&script.text, depth + 1, report);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn assess_git(flags: &[&str], operands: &[&str], report: &mut RiskReport) {
+    match operands.first().copied() {
+        Some("push") => {
+            let forced = flags.contains(&"--force")
+                || has_short_flag(flags, 'f')
+                || operands
+                    .iter()
+                    .skip(1)
+                    .any(|refspec| refspec.starts_with('+'));
+            if forced {
+                report.flag(
+                    RiskLevel::High,
+                    "force-pushes and can overwrite remote history",
+                );
+            } else if flags
+                .iter()
+                .any(|flag| flag.starts_with("--force-with-lease"))
+            {
+                report.flag(RiskLevel::Medium, "force-pushes with lease");
+            }
+        }
+        Some("reset") if flags.contains(&"--hard") => {
+            report.flag(
+                RiskLevel::Medium,
+                "discards uncommitted changes (reset --hard)",
+            );
+        }
+        Some("clean")
+            if has_short_flag(flags, 'f')
+                && !has_short_flag(flags, 'n')
+                && !flags.contains(&"--dry-run") =>
+        {
+            report.flag(RiskLevel::Medium, "deletes untracked files (git clean)");
+        }
+        _ => {}
+    }
+}
+
+/// `args` from the subcommand on, past git's own options such as
+/// `-C repo` or `--git-dir=…`.
+fn git_subcommand(mut args: &[Word]) -> &[Word] {
+    while let Some(first) = args.first() {
+        let text = first.text.as_str();
+        if !text.starts_with('-') || text == "--" {
+            break;
+        }
+        let skip = if GIT_VALUE_OPTIONS.contains(&text) {
+            2
+        } else {
+            1
+        };
+        args = &args[skip.min(args.len())..];
+    }
+    args
+}
+
+fn partition_args(args: &[Word]) -> (Vec<&str>, Vec<&str>) {
+    let mut flags = Vec::new();
+    let mut operands = Vec::new();
+    let mut options_done = false;
+
+    for word in args {
+        let text = word.text.as_str();
+        if options_done {
+            operands.push(text);
+        } else if text == "--" {
+            options_done = true;
+        } else if text.starts_with('-') && text.len() > 1 && !word.is_quoted() {
+            flags.push(text);
+        } else {
+            operands.push(text);
+        }
+    }
+
+    (flags, operands)
+}
+
+fn has_short_flag(flags: &[&str], flag: char) -> bool {
+    flags.iter().any(|candidate| {
+        candidate
+            .strip_prefix('-')
+            .filter(|rest| !rest.starts_with('-'))
+            .is_some_and(|cluster| cluster.contains(flag))
+    })
+}
+
+fn is_sweeping_target(target: &str) -> bool {
+    let trimmed = target.trim_end_matches('/');
+    matches!(
+        trimmed,
+        "" | "/*" | "~" | "~/*" | "$HOME" | "${HOME}" | "$HOME/*" | "${HOME}/*"
+    ) || is_system_path(target)
+}
+
+fn is_system_path(target: &str) -> bool {
+    if HARMLESS_DEVICES.contains(&target) || target.starts_with("/dev/fd/") {
+        return false;
+    }
+    if target.len() > 1 && target.trim_end_matches('/').is_empty() {
+        return true;
+    }
+    SYSTEM_PATHS.iter().any(|root| {
+        target == *root
+            || target
+                .strip_prefix(root)
+                .is_some_and(|rest| rest.starts_with('/'))
+    })
+}
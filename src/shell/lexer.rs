@@ -0,0 +1,792 @@
+use super::{List, ParseError, RedirectOp, Substitution, Word, WordPart, parser};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum Token {
+    Word(Word),
+    /// The digits in `2>` or `0<`.
+    IoNumber(u32),
+    Op(Op),
+    Newline,
+    /// `(( … ))` where a command starts, with the expression as written.
+    Arithmetic(String),
+    Eof,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Op {
+    Semi,
+    DoubleSemi,
+    SemiAmp,
+    DoubleSemiAmp,
+    Amp,
+    AndIf,
+    OrIf,
+    Pipe,
+    PipeAll,
+    LParen,
+    RParen,
+    Redirect(RedirectOp),
+}
+
+impl Op {
+    pub(super) fn as_str(self) -> &'static str {
+        match self {
+            Op::Semi => ";",
+            Op::DoubleSemi => ";;",
+            Op::SemiAmp => ";&",
+            Op::DoubleSemiAmp => ";;&",
+            Op::Amp => "&",
+            Op::AndIf => "&&",
+            Op::OrIf => "||",
+            Op::Pipe => "|",
+            Op::PipeAll => "|&",
+            Op::LParen => "(",
+            Op::RParen => ")",
+            Op::Redirect(op) => op.as_str(),
+        }
+    }
+}
+
+pub(super) struct Lexer {
+    chars: Vec<char>,
+    /// The byte offset of each character, and of the end of the source.
+    offsets: Vec<usize>,
+    index: usize,
+    /// Where the line holding pending heredocs ends, and where lexing resumes
+    /// once their bodies are skipped.
+    heredoc_skip: Option<(usize, usize)>,
+    /// How many commands and substitutions enclose the current token, kept
+    /// up to date by the parser.
+    pub(super) depth: usize,
+    pub(super) errors: Vec<ParseError>,
+}
+
+impl Lexer {
+    pub(super) fn new(source: &str, depth: usize) -> Self {
+        let offsets = source
+            .char_indices()
+            .map(|(offset, _)| offset)
+            .chain([source.len()])
+            .collect();
+        Self {
+            chars: source.chars().collect(),
+            offsets,
+            index: 0,
+            heredoc_skip: None,
+            depth,
+            errors: Vec::new(),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.index).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.index + offset).copied()
+    }
+
+    /// The byte offset of a character index.
+    pub(super) fn offset(&self, index: usize) -> usize {
+        self.offsets[index.min(self.chars.len())]
+    }
+
+    fn error(&mut self, message: impl Into<String>, index: usize) {
+        let offset = self.offset(index);
+        self.errors.push(ParseError {
+            message: message.into(),
+            offset,
+        });
+    }
+
+    /// The next token and the byte offset it starts at.
+    pub(super) fn next_token(&mut self) -> (Token, usize) {
+        loop {
+            let start = self.index;
+            let offset = self.offset(start);
+            let Some(c) = self.peek() else {
+                return (Token::Eof, offset);
+            };
+            let token = match c {
+                ' ' | '\t' | '\r' => {
+                    self.index += 1;
+                    continue;
+                }
+                '\\' if self.peek_at(1) == Some('\n') => {
+                    self.index += 2;
+                    continue;
+                }
+                '#' => {
+                    while self.peek().is_some_and(|c| c != '\n') {
+                        self.index += 1;
+                    }
+                    continue;
+                }
+                '\n' => {
+                    self.index += 1;
+                    if let Some((line_end, resume)) = self.heredoc_skip {
+                        if start >= line_end {
+                            self.index = resume;
+                            self.heredoc_skip = None;
+                        }
+                    }
+                    Token::Newline
+                }
+                ';' => Token::Op(self.read_op(&[
+                    (";;&", Op::DoubleSemiAmp),
+                    (";;", Op::DoubleSemi),
+                    (";&", Op::SemiAmp),
+                    (";", Op::Semi),
+                ])),
+                '|' => Token::Op(self.read_op(&[
+                    ("||", Op::OrIf),
+                    ("|&", Op::PipeAll),
+                    ("|", Op::Pipe),
+                ])),
+                '&' => Token::Op(self.read_op(&[
+                    ("&&", Op::AndIf),
+                    ("&>>", Op::Redirect(RedirectOp::AppendAll)),
+                    ("&>", Op::Redirect(RedirectOp::WriteAll)),
+                    ("&", Op::Amp),
+                ])),
+                '(' if self.peek_at(1) == Some('(') && self.is_arithmetic_command() => {
+                    let inner = self.read_balanced('(', ')');
+                    let inner = inner.strip_prefix('(').unwrap_or(&inner);
+                    Token::Arithmetic(inner.strip_suffix(')').unwrap_or(inner).to_string())
+                }
+                '(' => {
+                    self.index += 1;
+                    Token::Op(Op::LParen)
+                }
+                ')' => {
+                    self.index += 1;
+                    Token::Op(Op::RParen)
+                }
+                '<' | '>' if self.peek_at(1) != Some('(') => {
+                    Token::Op(Op::Redirect(self.read_redirect_op()))
+                }
+                _ => {
+                    let word = self.read_word();
+                    match self.io_number(&word) {
+                        Some(fd) => Token::IoNumber(fd),
+                        None => Token::Word(word),
+                    }
+                }
+            };
+            return (token, offset);
+        }
+    }
+
+    fn read_op(&mut self, candidates: &[(&str, Op)]) -> Op {
+        for (text, op) in candidates {
+            let matches = text
+                .chars()
+                .enumerate()
+                .all(|(offset, c)| self.peek_at(offset) == Some(c));
+            if matches {
+                self.index += text.chars().count();
+                return *op;
+            }
+        }
+        unreachable!("the last candidate is the single character at the cursor")
+    }
+
+    fn read_redirect_op(&mut self) -> RedirectOp {
+        let candidates: &[(&str, RedirectOp)] = &[
+            ("<<<", RedirectOp::HereString),
+            ("<<-", RedirectOp::HeredocStripTabs),
+            ("<<", RedirectOp::Heredoc),
+            ("<&", RedirectOp::DupRead),
+            ("<>", RedirectOp::ReadWrite),
+            ("<", RedirectOp::Read),
+            (">>", RedirectOp::Append),
+            (">&", RedirectOp::DupWrite),
+            (">|", RedirectOp::Clobber),
+            (">", RedirectOp::Write),
+        ];
+        let ops: Vec<(&str, Op)> = candidates
+            .iter()
+            .map(|(text, op)| (*text, Op::Redirect(*op)))
+            .collect();
+        match self.read_op(&ops) {
+            Op::Redirect(op) => op,
+            _ => unreachable!("only redirections are candidates"),
+        }
+    }
+
+    /// `((` starts arithmetic when its inner `(` closes right before the
+    /// outer one, as in `((i++))`, and a nested subshell otherwise, as in
+    /// `((cd src); make)`.
+    fn is_arithmetic_command(&self) -> bool {
+        let mut depth = 0;
+        let mut inner_close = None;
+        for (offset, &c) in self.chars[self.index..].iter().enumerate() {
+            match c {
+                '(' => depth += 1,
+                ')' => {
+                    depth -= 1;
+                    if depth == 1 && inner_close.is_none() {
+                        inner_close = Some(offset);
+                    }
+                    if depth == 0 {
+                        return inner_close.is_some_and(|close| close + 1 == offset);
+                    }
+                }
+                _ => {}
+            }
+        }
+        false
+    }
+
+    fn io_number(&self, word: &Word) -> Option<u32> {
+        let redirects_next = matches!(self.peek(), Some('<' | '>')) && self.peek_at(1) != Some('(');
+        if !redirects_next || word.raw.is_empty() || !word.raw.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        word.raw.parse().ok()
+    }
+
+    fn read_word(&mut self) -> Word {
+        let start = self.index;
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let flush = |literal: &mut String, parts: &mut Vec<WordPart>| {
+            if !literal.is_empty() {
+                parts.push(WordPart::Literal(std::mem::take(literal)));
+            }
+        };
+
+        while let Some(c) = self.peek() {
+            match c {
+                ' ' | '\t' | '\r' | '\n' | ';' | '|' | '&' | ')' => break,
+                '(' if parts.is_empty() && is_assignment_prefix(&literal) => {
+                    // `list=(a b c)` assigns an array.
+                    let inner = self.read_balanced('(', ')');
+                    literal.push_str(&format!("({inner})"));
+                }
+                '(' => break,
+                '<' | '>' if self.peek_at(1) == Some('(') => {
+                    flush(&mut literal, &mut parts);
+                    let at = self.index;
+                    self.index += 1;
+                    let source = self.read_balanced('(', ')');
+                    let substitution = self.substitution(source, at);
+                    parts.push(WordPart::ProcessSubstitution(c, substitution));
+                }
+                '<' | '>' => break,
+                '\\' => {
+                    self.index += 1;
+                    match self.peek() {
+                        Some('\n') => self.index += 1,
+                        Some(escaped) => {
+                            flush(&mut literal, &mut parts);
+                            parts.push(WordPart::Escaped(escaped));
+                            self.index += 1;
+                        }
+                        None => literal.push('\\'),
+                    }
+                }
+                '\'' => {
+                    flush(&mut literal, &mut parts);
+                    let text = self.read_single_quoted();
+                    parts.push(WordPart::SingleQuoted(text));
+                }
+                '"' => {
+                    flush(&mut literal, &mut parts);
+                    let inner = self.read_double_quoted();
+                    parts.push(WordPart::DoubleQuoted(inner));
+                }
+                '$' => match self.read_dollar(false) {
+                    Some(part) => {
+                        flush(&mut literal, &mut parts);
+                        parts.push(part);
+                    }
+                    None => {
+                        literal.push('$');
+                        self.index += 1;
+                    }
+                },
+                '`' => {
+                    flush(&mut literal, &mut parts);
+                    parts.push(WordPart::Backticks(self.read_backticks()));
+                }
+                _ => {
+                    literal.push(c);
+                    self.index += 1;
+                }
+            }
+        }
+        flush(&mut literal, &mut parts);
+
+        Word {
+            raw: self.chars[start..self.index].iter().collect(),
+            text: parts.iter().map(WordPart::text).collect(),
+            parts,
+        }
+    }
+
+    fn read_single_quoted(&mut self) -> String {
+        let start = self.index;
+        self.index += 1;
+        let mut text = String::new();
+        loop {
+            match self.peek() {
+                Some('\'') => {
+                    self.index += 1;
+                    return text;
+                }
+                Some(c) => {
+                    text.push(c);
+                    self.index += 1;
+                }
+                None => {
+                    self.error("unterminated single quote", start);
+                    return text;
+                }
+            }
+        }
+    }
+
+    fn read_double_quoted(&mut self) -> Vec<WordPart> {
+        let start = self.index;
+        self.index += 1;
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let flush = |literal: &mut String, parts: &mut Vec<WordPart>| {
+            if !literal.is_empty() {
+                parts.push(WordPart::Literal(std::mem::take(literal)));
+            }
+        };
+
+        loop {
+            match self.peek() {
+                None => {
+                    self.error("unterminated double quote", start);
+                    break;
+                }
+                Some('"') => {
+                    self.index += 1;
+                    break;
+                }
+                Some('\\') => match self.peek_at(1) {
+                    Some('\n') => self.index += 2,
+                    Some(escaped @ ('$' | '`' | '"' | '\\')) => {
+                        literal.push(escaped);
+                        self.index += 2;
+                    }
+                    _ => {
+                        literal.push('\\');
+                        self.index += 1;
+                    }
+                },
+                Some('$') => match self.read_dollar(true) {
+                    Some(part) => {
+                        flush(&mut literal, &mut parts);
+                        parts.push(part);
+                    }
+                    None => {
+                        literal.push('$');
+                        self.index += 1;
+                    }
+                },
+                Some('`') => {
+                    flush(&mut literal, &mut parts);
+                    parts.push(WordPart::Backticks(self.read_backticks()));
+                }
+                Some(c) => {
+                    literal.push(c);
+                    self.index += 1;
+                }
+            }
+        }
+        flush(&mut literal, &mut parts);
+        parts
+    }
+
+    /// Reads the expansion at a `$`, or returns `None` when the `$` is literal.
+    fn read_dollar(&mut self, in_double_quotes: bool) -> Option<WordPart> {
+        let at = self.index;
+        match self.peek_at(1)? {
+            '(' => {
+                self.index += 1;
+                let inner = self.read_balanced('(', ')');
+                if is_wrapped_in_parens(&inner) {
+                    return Some(WordPart::Arithmetic(inner[1..inner.len() - 1].to_string()));
+                }
+                Some(WordPart::CommandSubstitution(self.substitution(inner, at)))
+            }
+            '{' => {
+                self.index += 1;
+                let inner = self.read_balanced('{', '}');
+                Some(WordPart::Parameter(format!("${{{inner}}}")))
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                self.index += 1;
+                let mut name = String::from("$");
+                while let Some(c) = self
+                    .peek()
+                    .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+                {
+                    name.push(c);
+                    self.index += 1;
+                }
+                Some(WordPart::Parameter(name))
+            }
+            c if c.is_ascii_digit() || "@*#?-$!".contains(c) => {
+                self.index += 2;
+                Some(WordPart::Parameter(format!("${c}")))
+            }
+            '\'' if !in_double_quotes => {
+                self.index += 1;
+                Some(WordPart::SingleQuoted(self.read_ansi_c_quoted()))
+            }
+            '"' if !in_double_quotes => {
+                self.index += 1;
+                Some(WordPart::DoubleQuoted(self.read_double_quoted()))
+            }
+            _ => None,
+        }
+    }
+
+    /// `$'…'` with the common backslash escapes resolved.
+    fn read_ansi_c_quoted(&mut self) -> String {
+        let start = self.index;
+        self.index += 1;
+        let mut text = String::new();
+        loop {
+            match self.peek() {
+                None => {
+                    self.error("unterminated single quote", start);
+                    return text;
+                }
+                Some('\'') => {
+                    self.index += 1;
+                    return text;
+                }
+                Some('\\') => {
+                    let escaped = self.peek_at(1);
+                    self.index += 1 + usize::from(escaped.is_some());
+                    match escaped {
+                        Some('n') => text.push('\n'),
+                        Some('t') => text.push('\t'),
+                        Some('r') => text.push('\r'),
+                        Some('e' | 'E') => text.push('\x1b'),
+                        Some('0') => text.push('\0'),
+                        Some(other) if "\\'\"?".contains(other) => text.push(other),
+                        Some(other) => {
+                            text.push('\\');
+                            text.push(other);
+                        }
+                        None => text.push('\\'),
+                    }
+                }
+                Some(c) => {
+                    text.push(c);
+                    self.index += 1;
+                }
+            }
+        }
+    }
+
+    fn read_backticks(&mut self) -> Substitution {
+        let start = self.index;
+        self.index += 1;
+        let mut inner = String::new();
+        loop {
+            match self.peek() {
+                None => {
+                    self.error("unterminated backtick", start);
+                    break;
+                }
+                Some('`') => {
+                    self.index += 1;
+                    break;
+                }
+                Some('\\') => {
+                    match self.peek_at(1) {
+                        Some(escaped @ ('$' | '`' | '\\')) => inner.push(escaped),
+                        Some(other) => {
+                            inner.push('\\');
+                            inner.push(other);
+                        }
+                        None => inner.push('\\'),
+                    }
+                    self.index += 1 + usize::from(self.peek_at(1).is_some());
+                }
+                Some(c) => {
+                    inner.push(c);
+                    self.index += 1;
+                }
+            }
+        }
+        self.substitution(inner, start)
+    }
+
+    /// Reads from an opening delimiter to its match, returning the inside.
+    fn read_balanced(&mut self, open: char, close: char) -> String {
+        let start = self.index;
+        let mut depth = 0;
+        let mut inner = String::new();
+        let mut quote: Option<char> = None;
+
+        while let Some(c) = self.peek() {
+            self.index += 1;
+            match quote {
+                Some(q) => {
+                    if c == q {
+                        quote = None;
+                    } else if c == '\\' && q == '"' {
+                        inner.push(c);
+                        if let Some(next) = self.peek() {
+                            inner.push(next);
+                            self.index += 1;
+                        }
+                        continue;
+                    }
+                }
+                None if c == '\\' => {
+                    inner.push(c);
+                    if let Some(next) = self.peek() {
+                        inner.push(next);
+                        self.index += 1;
+                    }
+                    continue;
+                }
+                None if c == '\'' || c == '"' => quote = Some(c),
+                None if c == open => {
+                    depth += 1;
+                    if depth == 1 {
+                        continue;
+                    }
+                }
+                None if c == close => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return inner;
+                    }
+                }
+                None => {}
+            }
+            inner.push(c);
+        }
+
+        self.error(format!("missing `{close}`"), start);
+        inner
+    }
+
+    fn substitution(&mut self, source: String, at: usize) -> Substitution {
+        if self.depth >= parser::MAX_DEPTH {
+            self.error("commands nested too deeply", at);
+            return Substitution {
+                source,
+                body: List::default(),
+            };
+        }
+        let (body, errors) = parser::parse(&source, self.depth + 1);
+        let base = self.offset(at);
+        self.errors
+            .extend(errors.into_iter().map(|error| ParseError {
+                message: error.message,
+                offset: base + error.offset,
+            }));
+        Substitution { source, body }
+    }
+
+    /// Reads the body of a heredoc whose operator is on the current line. The
+    /// body starts on the next line, after any earlier heredoc on this line,
+    /// and is skipped when the lexer reaches the end of the line.
+    pub(super) fn read_heredoc(&mut self, delimiter: &str, strip_tabs: bool) -> String {
+        let (line_end, mut position) = match self.heredoc_skip {
+            Some(skip) => skip,
+            None => {
+                let line_end = self.chars[self.index..]
+                    .iter()
+                    .position(|&c| c == '\n')
+                    .map_or(self.chars.len(), |offset| self.index + offset);
+                (line_end, (line_end + 1).min(self.chars.len()))
+            }
+        };
+
+        let mut body = String::new();
+        while position < self.chars.len() {
+            let end = self.chars[position..]
+                .iter()
+                .position(|&c| c == '\n')
+                .map_or(self.chars.len(), |offset| position + offset);
+            let line: String = self.chars[position..end].iter().collect();
+            position = (end + 1).min(self.chars.len());
+
+            let line = if strip_tabs {
+                line.trim_start_matches('\t')
+            } else {
+                line.as_str()
+            };
+            if line == delimiter {
+                break;
+            }
+            body.push_str(line);
+            body.push('\n');
+        }
+
+        self.heredoc_skip = Some((line_end, position));
+        body
+    }
+}
+
+fn is_assignment_prefix(literal: &str) -> bool {
+    let name = literal
+        .strip_suffix("+=")
+        .or_else(|| literal.strip_suffix('='));
+    name.is_some_and(|name| {
+        !name.is_empty()
+            && !name.starts_with(|c: char| c.is_ascii_digit())
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    })
+}
+
+/// Whether `inner` is one parenthesised group, so `$(…)` around it is
+/// `$((…))` arithmetic rather than a command substitution.
+fn is_wrapped_in_parens(inner: &str) -> bool {
+    if !inner.starts_with('(') || !inner.ends_with(')') {
+        return false;
+    }
+    let mut depth = 0;
+    for (index, c) in inner.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return index == inner.len() - 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(source: &str) -> Vec<Token> {
+        let mut lexer = Lexer::new(source, 0);
+        let mut tokens = Vec::new();
+        loop {
+            match lexer.next_token().0 {
+                Token::Eof => return tokens,
+                token => tokens.push(token),
+            }
+        }
+    }
+
+    fn ops(source: &str) -> Vec<&'static str> {
+        tokens(source)
+            .into_iter()
+            .filter_map(|token| match token {
+                Token::Op(op) => Some(op.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn operators_take_the_longest_match() {
+        assert_eq!(
+            ops("a && b || c | d |& e ; f ;; g ;& h ;;& i & j"),
+            vec!["&&", "||", "|", "|&", ";", ";;", ";&", ";;&", "&"]
+        );
+        assert_eq!(
+            ops("a < b > c >> d >| e <> f <& g >& h &> i &>> j <<< k"),
+            vec!["<", ">", ">>", ">|", "<>", "<&", ">&", "&>", "&>>", "<<<"]
+        );
+    }
+
+    #[test]
+    fn digits_before_a_redirection_are_an_io_number() {
+        assert_eq!(
+            tokens("cmd 2>&1"),
+            vec![
+                Token::Word(Word::literal("cmd")),
+                Token::IoNumber(2),
+                Token::Op(Op::Redirect(RedirectOp::DupWrite)),
+                Token::Word(Word::literal("1")),
+            ]
+        );
+        assert_eq!(tokens("echo 2 >x")[1], Token::Word(Word::literal("2")));
+    }
+
+    #[test]
+    fn comments_run_to_the_end_of_the_line() {
+        assert_eq!(
+            tokens("ls # list files\npwd"),
+            vec![
+                Token::Word(Word::literal("ls")),
+                Token::Newline,
+                Token::Word(Word::literal("pwd")),
+            ]
+        );
+        assert_eq!(tokens("echo a#b")[1], Token::Word(Word::literal("a#b")));
+    }
+
+    #[test]
+    fn double_parens_are_arithmetic_or_nested_subshells() {
+        assert_eq!(
+            tokens("((i += 1))"),
+            vec![Token::Arithmetic("i += 1".to_string())]
+        );
+        assert_eq!(tokens("((cd src); make)")[0], Token::Op(Op::LParen));
+    }
+
+    #[test]
+    fn words_are_split_into_parts() {
+        let Token::Word(word) = tokens(r#"a\ 'c d'"$e f"$(g)`h`$((1+2))"#).remove(0) else {
+            panic!("expected a word");
+        };
+        assert_eq!(word.parts.len(), 7);
+        assert_eq!(word.parts[0], WordPart::Literal("a".to_string()));
+        assert_eq!(word.parts[1], WordPart::Escaped(' '));
+        assert_eq!(word.parts[2], WordPart::SingleQuoted("c d".to_string()));
+        assert_eq!(
+            word.parts[3],
+            WordPart::DoubleQuoted(vec![
+                WordPart::Parameter("$e".to_string()),
+                WordPart::Literal(" f".to_string()),
+            ])
+        );
+        assert!(matches!(&word.parts[4], WordPart::CommandSubstitution(s) if s.source == "g"));
+        assert!(matches!(&word.parts[5], WordPart::Backticks(s) if s.source == "h"));
+        assert_eq!(word.parts[6], WordPart::Arithmetic("1+2".to_string()));
+        assert_eq!(word.text, "a c d$e f$(g)`h`$((1+2))");
+    }
+
+    #[test]
+    fn ansi_c_quotes_resolve_escapes() {
+        let Token::Word(word) = tokens(r"$'a\tb\n'").remove(0) else {
+            panic!("expected a word");
+        };
+        assert_eq!(word.text, "a\tb\n");
+    }
+
+    #[test]
+    fn array_assignments_stay_one_word() {
+        assert_eq!(
+            tokens("files=(a.txt 'b c.txt')"),
+            vec![Token::Word(Word {
+                raw: "files=(a.txt 'b c.txt')".to_string(),
+                text: "files=(a.txt 'b c.txt')".to_string(),
+                parts: vec![WordPart::Literal("files=(a.txt 'b c.txt')".to_string())],
+            })]
+        );
+    }
+
+    #[test]
+    fn unterminated_quotes_are_reported() {
+        let mut lexer = Lexer::new("echo 'oops", 0);
+        while lexer.next_token().0 != Token::Eof {}
+        assert_eq!(lexer.errors.len(), 1);
+        assert_eq!(lexer.errors[0].message, "unterminated single quote");
+        assert_eq!(lexer.errors[0].offset, 5);
+    }
+}
@@ -0,0 +1,571 @@
+mod lexer;
+mod parser;
+
+use std::fmt;
+
+/// How many levels of substitutions inside substitutions analyzers follow.
+pub const MAX_NESTING: usize = 4;
+
+/// Commands separated by `;`, `&` or newlines.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct List {
+    pub items: Vec<ListItem>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListItem {
+    pub and_or: AndOr,
+    /// Ended with `&`.
+    pub background: bool,
+}
+
+/// Pipelines joined by `&&` and `||`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AndOr {
+    pub first: Pipeline,
+    pub rest: Vec<(LogicalOp, Pipeline)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogicalOp {
+    And,
+    Or,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pipeline {
+    /// Prefixed with `!`.
+    pub negated: bool,
+    pub commands: Vec<Command>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Simple(SimpleCommand),
+    Compound(CompoundCommand),
+    Function(FunctionDef),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SimpleCommand {
+    /// Leading `NAME=value` words.
+    pub assignments: Vec<Word>,
+    /// The program and its arguments.
+    pub words: Vec<Word>,
+    pub redirects: Vec<Redirect>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompoundCommand {
+    pub kind: Compound,
+    pub redirects: Vec<Redirect>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Compound {
+    /// `( … )`
+    Subshell(List),
+    /// `{ …; }`
+    Group(List),
+    If {
+        /// Each `if` or `elif` condition with the body it guards.
+        branches: Vec<(List, List)>,
+        otherwise: Option<List>,
+    },
+    While {
+        condition: List,
+        body: List,
+    },
+    Until {
+        condition: List,
+        body: List,
+    },
+    For {
+        variable: String,
+        /// `None` when there is no `in`, so the loop walks `"$@"`.
+        words: Option<Vec<Word>>,
+        body: List,
+    },
+    Select {
+        variable: String,
+        words: Option<Vec<Word>>,
+        body: List,
+    },
+    /// `for ((init; test; step))`, with the header as written.
+    ArithmeticFor {
+        header: String,
+        body: List,
+    },
+    Case {
+        subject: Word,
+        arms: Vec<CaseArm>,
+    },
+    /// `(( … ))`, with the expression as written.
+    Arithmetic(String),
+    /// `[[ … ]]`, with operators kept as plain words.
+    Test(Vec<Word>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaseArm {
+    pub patterns: Vec<Word>,
+    pub body: List,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionDef {
+    pub name: String,
+    pub body: Box<Command>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Word {
+    /// The word exactly as written.
+    pub raw: String,
+    /// The word with quotes and escapes removed. Expansions are kept as
+    /// written, since their values are not known.
+    pub text: String,
+    pub parts: Vec<WordPart>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WordPart {
+    Literal(String),
+    SingleQuoted(String),
+    DoubleQuoted(Vec<WordPart>),
+    /// A backslash-escaped character outside quotes.
+    Escaped(char),
+    /// As written, such as `$HOME`, `$1` or `${name:-default}`.
+    Parameter(String),
+    /// `$( … )`
+    CommandSubstitution(Substitution),
+    /// `` ` … ` ``
+    Backticks(Substitution),
+    /// `$(( … ))`, with the expression as written.
+    Arithmetic(String),
+    /// `<( … )` or `>( … )`; the character is the direction.
+    ProcessSubstitution(char, Substitution),
+}
+
+/// A command nested inside a word, parsed like any other.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Substitution {
+    pub source: String,
+    pub body: List,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Redirect {
+    pub fd: Option<u32>,
+    pub op: RedirectOp,
+    /// The file, descriptor or heredoc delimiter.
+    pub target: Word,
+    pub heredoc: Option<Heredoc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedirectOp {
+    Read,
+    Write,
+    Append,
+    Clobber,
+    ReadWrite,
+    DupRead,
+    DupWrite,
+    Heredoc,
+    HeredocStripTabs,
+    HereString,
+    WriteAll,
+    AppendAll,
+}
+
+impl RedirectOp {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RedirectOp::Read => "<",
+            RedirectOp::Write => ">",
+            RedirectOp::Append => ">>",
+            RedirectOp::Clobber => ">|",
+            RedirectOp::ReadWrite => "<>",
+            RedirectOp::DupRead => "<&",
+            RedirectOp::DupWrite => ">&",
+            RedirectOp::Heredoc => "<<",
+            RedirectOp::HeredocStripTabs => "<<-",
+            RedirectOp::HereString => "<<<",
+            RedirectOp::WriteAll => "&>",
+            RedirectOp::AppendAll => "&>>",
+        }
+    }
+
+    /// Whether the target is a file that gets written, as opposed to read or
+    /// duplicated from another descriptor.
+    pub fn writes(self) -> bool {
+        matches!(
+            self,
+            RedirectOp::Write
+                | RedirectOp::Append
+                | RedirectOp::Clobber
+                | RedirectOp::ReadWrite
+                | RedirectOp::WriteAll
+                | RedirectOp::AppendAll
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heredoc {
+    pub body: String,
+    /// False when the delimiter was quoted, so the body is taken literally.
+    pub expands: bool,
+}
+
+/// Where parsing failed, with a byte offset into the source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub message: String,
+    pub offset: usize,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at byte {}", self.message, self.offset)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parses sh, bash or zsh source, failing on the first syntax error.
+pub fn parse(source: &str) -> Result<List, ParseError> {
+    let (list, mut errors) = parser::parse(source, 0);
+    if errors.is_empty() {
+        Ok(list)
+    } else {
+        Err(errors.remove(0))
+    }
+}
+
+/// Parses as much as it can, closing unterminated quotes and constructs at
+/// the end and skipping stray tokens. Generated commands are not always
+/// valid, and checks on them should still see what is there.
+pub fn parse_partial(source: &str) -> List {
+    parser::parse(source, 0).0
+}
+
+/// How a simple command is joined to the one that ran before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connector {
+    Start,
+    Pipe,
+    And,
+    Or,
+    Sequence,
+    Background,
+}
+
+/// A simple command in the order it appears, flattened out of any compound
+/// commands around it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stage<'a> {
+    pub connector: Connector,
+    pub command: &'a SimpleCommand,
+}
+
+impl List {
+    /// Every simple command, including those inside subshells, groups,
+    /// conditionals, loops and function bodies, but not substitutions.
+    pub fn stages(&self) -> Vec<Stage<'_>> {
+        let mut stages = Vec::new();
+        let mut connector = Connector::Start;
+        collect_stages(self, &mut connector, &mut stages);
+        stages
+    }
+
+    /// Every pipeline, outermost first, not looking into substitutions.
+    pub fn pipelines(&self) -> Vec<&Pipeline> {
+        let mut pipelines = Vec::new();
+        collect_pipelines(self, &mut pipelines);
+        pipelines
+    }
+
+    /// Every command, compound ones before the commands inside them.
+    pub fn commands(&self) -> Vec<&Command> {
+        self.pipelines()
+            .into_iter()
+            .flat_map(|pipeline| &pipeline.commands)
+            .collect()
+    }
+
+    /// Substitutions in any word of this list. Their own bodies are not
+    /// searched; walk `Substitution::body` for that.
+    pub fn substitutions(&self) -> Vec<&Substitution> {
+        self.commands()
+            .into_iter()
+            .flat_map(Command::words)
+            .flat_map(Word::substitutions)
+            .collect()
+    }
+
+    /// Redirections on simple and compound commands alike.
+    pub fn redirects(&self) -> Vec<&Redirect> {
+        self.commands()
+            .into_iter()
+            .flat_map(Command::redirects)
+            .collect()
+    }
+}
+
+impl Command {
+    /// Words that belong to this command itself, not to commands nested in it.
+    pub fn words(&self) -> Vec<&Word> {
+        let mut words: Vec<&Word> = match self {
+            Command::Simple(simple) => simple.assignments.iter().chain(&simple.words).collect(),
+            Command::Compound(compound) => match &compound.kind {
+                Compound::For { words, .. } | Compound::Select { words, .. } => {
+                    words.iter().flatten().collect()
+                }
+                Compound::Case { subject, arms } => std::iter::once(subject)
+                    .chain(arms.iter().flat_map(|arm| &arm.patterns))
+                    .collect(),
+                Compound::Test(words) => words.iter().collect(),
+                _ => Vec::new(),
+            },
+            Command::Function(_) => Vec::new(),
+        };
+        words.extend(self.redirects().iter().map(|redirect| &redirect.target));
+        words
+    }
+
+    pub fn redirects(&self) -> &[Redirect] {
+        match self {
+            Command::Simple(simple) => &simple.redirects,
+            Command::Compound(compound) => &compound.redirects,
+            Command::Function(_) => &[],
+        }
+    }
+}
+
+impl SimpleCommand {
+    pub fn program(&self) -> Option<&str> {
+        self.words.first().map(|word| word.text.as_str())
+    }
+
+    /// Substitutions in the words and redirection targets of this command.
+    pub fn substitutions(&self) -> Vec<&Substitution> {
+        self.assignments
+            .iter()
+            .chain(&self.words)
+            .chain(self.redirects.iter().map(|redirect| &redirect.target))
+            .flat_map(Word::substitutions)
+            .collect()
+    }
+}
+
+impl Word {
+    /// An unquoted word with no expansions.
+    pub fn literal(text: &str) -> Self {
+        Self {
+            raw: text.to_string(),
+            text: text.to_string(),
+            parts: vec![WordPart::Literal(text.to_string())],
+        }
+    }
+
+    /// Whether any part of the word was quoted or escaped.
+    pub fn is_quoted(&self) -> bool {
+        self.parts.iter().any(|part| {
+            matches!(
+                part,
+                WordPart::SingleQuoted(_) | WordPart::DoubleQuoted(_) | WordPart::Escaped(_)
+            )
+        })
+    }
+
+    /// Command, backtick and process substitutions, including those inside
+    /// double quotes.
+    pub fn substitutions(&self) -> Vec<&Substitution> {
+        let mut found = Vec::new();
+        collect_substitutions(&self.parts, &mut found);
+        found
+    }
+
+    /// Expansions outside double quotes, whose values undergo word splitting
+    /// and globbing. Arithmetic is left out since it yields one number.
+    pub fn unquoted_expansions(&self) -> Vec<&WordPart> {
+        self.parts
+            .iter()
+            .filter(|part| {
+                matches!(
+                    part,
+                    WordPart::Parameter(_)
+                        | WordPart::CommandSubstitution(_)
+                        | WordPart::Backticks(_)
+                )
+            })
+            .collect()
+    }
+}
+
+impl WordPart {
+    /// The part as it would read with its quotes removed.
+    pub fn text(&self) -> String {
+        match self {
+            WordPart::Literal(text) | WordPart::SingleQuoted(text) => text.clone(),
+            WordPart::DoubleQuoted(parts) => parts.iter().map(WordPart::text).collect(),
+            WordPart::Escaped(c) => c.to_string(),
+            WordPart::Parameter(written) => written.clone(),
+            WordPart::CommandSubstitution(substitution) => format!("$({})", substitution.source),
+            WordPart::Backticks(substitution) => format!("`{}`", substitution.source),
+            WordPart::Arithmetic(expression) => format!("$(({expression}))"),
+            WordPart::ProcessSubstitution(direction, substitution) => {
+                format!("{direction}({})", substitution.source)
+            }
+        }
+    }
+}
+
+fn collect_substitutions<'a>(parts: &'a [WordPart], found: &mut Vec<&'a Substitution>) {
+    for part in parts {
+        match part {
+            WordPart::DoubleQuoted(inner) => collect_substitutions(inner, found),
+            WordPart::CommandSubstitution(substitution)
+            | WordPart::Backticks(substitution)
+            | WordPart::ProcessSubstitution(_, substitution) => found.push(substitution),
+            _ => {}
+        }
+    }
+}
+
+fn collect_pipelines<'a>(list: &'a List, pipelines: &mut Vec<&'a Pipeline>) {
+    for item in &list.items {
+        let and_or = &item.and_or;
+        for pipeline in std::iter::once(&and_or.first).chain(and_or.rest.iter().map(|(_, p)| p)) {
+            pipelines.push(pipeline);
+            for command in &pipeline.commands {
+                for nested in nested_lists(command) {
+                    collect_pipelines(nested, pipelines);
+                }
+            }
+        }
+    }
+}
+
+fn collect_stages<'a>(list: &'a List, connector: &mut Connector, stages: &mut Vec<Stage<'a>>) {
+    for (index, item) in list.items.iter().enumerate() {
+        if index > 0 {
+            *connector = if list.items[index - 1].background {
+                Connector::Background
+            } else {
+                Connector::Sequence
+            };
+        }
+        collect_pipeline_stages(&item.and_or.first, connector, stages);
+        for (op, pipeline) in &item.and_or.rest {
+            *connector = match op {
+                LogicalOp::And => Connector::And,
+                LogicalOp::Or => Connector::Or,
+            };
+            collect_pipeline_stages(pipeline, connector, stages);
+        }
+    }
+}
+
+fn collect_pipeline_stages<'a>(
+    pipeline: &'a Pipeline,
+    connector: &mut Connector,
+    stages: &mut Vec<Stage<'a>>,
+) {
+    for (index, command) in pipeline.commands.iter().enumerate() {
+        if index > 0 {
+            *connector = Connector::Pipe;
+        }
+        match command {
+            Command::Simple(simple) => {
+                stages.push(Stage {
+                    connector: *connector,
+                    command: simple,
+                });
+                *connector = Connector::Sequence;
+            }
+            _ => {
+                for nested in nested_lists(command) {
+                    collect_stages(nested, connector, stages);
+                }
+            }
+        }
+    }
+}
+
+/// The lists directly inside a compound command or function, in source order.
+fn nested_lists(command: &Command) -> Vec<&List> {
+    match command {
+        Command::Simple(_) => Vec::new(),
+        Command::Function(function) => nested_lists(&function.body),
+        Command::Compound(compound) => match &compound.kind {
+            Compound::Subshell(body) | Compound::Group(body) => vec![body],
+            Compound::If {
+                branches,
+                otherwise,
+            } => branches
+                .iter()
+                .flat_map(|(condition, body)| [condition, body])
+                .chain(otherwise)
+                .collect(),
+            Compound::While { condition, body } | Compound::Until { condition, body } => {
+                vec![condition, body]
+            }
+            Compound::For { body, .. }
+            | Compound::Select { body, .. }
+            | Compound::ArithmeticFor { body, .. } => vec![body],
+            Compound::Case { arms, .. } => arms.iter().map(|arm| &arm.body).collect(),
+            Compound::Arithmetic(_) | Compound::Test(_) => Vec::new(),
+        },
+    }
+}
+
+/// Strips `sudo`, `env`, `xargs` and friends, returning the wrappers that were
+/// removed and the words of the program they eventually run.
+pub fn unwrap_wrappers(mut args: &[Word]) -> (Vec<String>, &[Word]) {
+    let mut wrappers = Vec::new();
+
+    loop {
+        let Some(first) = args.first() else {
+            return (wrappers, args);
+        };
+        let name = program_name(&first.text);
+        let options_with_values: &[&str] = match name.as_str() {
+            "sudo" => &["-u", "-g", "-h", "-p", "-C", "-D", "-r", "-t", "-U"],
+            "doas" => &["-u", "-C"],
+            "env" => &["-u", "-C", "-S"],
+            "xargs" => &["-I", "-n", "-P", "-L", "-d", "-a", "-E", "-s"],
+            "nice" => &["-n"],
+            "timeout" | "nohup" | "time" | "command" | "exec" | "stdbuf" => &[],
+            _ => return (wrappers, args),
+        };
+
+        args = &args[1..];
+        while let Some(word) = args.first() {
+            let text = word.text.as_str();
+            if options_with_values.contains(&text) {
+                args = &args[args.len().min(2)..];
+            } else if text.starts_with('-') || (name == "env" && text.contains('=')) {
+                args = &args[1..];
+            } else {
+                break;
+            }
+        }
+
+        if name == "timeout" && !args.is_empty() {
+            args = &args[1..];
+        }
+        wrappers.push(name);
+    }
+}
+
+/// The lowercased file name of a program, so `/usr/bin/Rm` compares as `rm`.
+pub fn program_name(program: &str) -> String {
+    program
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or(program)
+        .to_ascii_lowercase()
+}
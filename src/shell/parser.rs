@@ -0,0 +1,712 @@
+use super::lexer::{Lexer, Op, Token};
+use super::{
+    AndOr, CaseArm, Command, Compound, CompoundCommand, FunctionDef, Heredoc, List, ListItem,
+    LogicalOp, ParseError, Pipeline, Redirect, RedirectOp, SimpleCommand, Word,
+};
+use std::collections::VecDeque;
+
+/// Reserved words that end the list before them.
+const CLOSERS: &[&str] = &["then", "elif", "else", "fi", "do", "done", "esac", "}"];
+
+/// Commands and substitutions nested deeper than this are reported and
+/// skipped, so generated input cannot recurse the parser off the stack.
+pub(super) const MAX_DEPTH: usize = 100;
+
+/// Parses `source` with recovery, returning the tree and every error found,
+/// in source order. `depth` counts what encloses `source`, for substitutions.
+pub(super) fn parse(source: &str, depth: usize) -> (List, Vec<ParseError>) {
+    let mut parser = Parser {
+        lexer: Lexer::new(source, depth),
+        peeked: VecDeque::new(),
+        errors: Vec::new(),
+    };
+
+    let mut list = List::default();
+    loop {
+        list.items.extend(parser.parse_list().items);
+        let (token, offset) = parser.next();
+        if token == Token::Eof {
+            break;
+        }
+        parser.error(format!("unexpected {}", describe(&token)), offset);
+    }
+
+    let mut errors = parser.errors;
+    errors.append(&mut parser.lexer.errors);
+    errors.sort_by_key(|error| error.offset);
+    (list, errors)
+}
+
+struct Parser {
+    lexer: Lexer,
+    peeked: VecDeque<(Token, usize)>,
+    errors: Vec<ParseError>,
+}
+
+impl Parser {
+    fn peek_nth(&mut self, n: usize) -> &Token {
+        while self.peeked.len() <= n {
+            let token = self.lexer.next_token();
+            self.peeked.push_back(token);
+        }
+        &self.peeked[n].0
+    }
+
+    fn peek(&mut self) -> &Token {
+        self.peek_nth(0)
+    }
+
+    fn peek_offset(&mut self) -> usize {
+        self.peek();
+        self.peeked[0].1
+    }
+
+    fn next(&mut self) -> (Token, usize) {
+        match self.peeked.pop_front() {
+            Some(token) => token,
+            None => self.lexer.next_token(),
+        }
+    }
+
+    fn error(&mut self, message: String, offset: usize) {
+        self.errors.push(ParseError { message, offset });
+    }
+
+    fn peek_is_reserved(&mut self, word: &str) -> bool {
+        is_reserved(self.peek(), word)
+    }
+
+    fn skip_newlines(&mut self) {
+        while *self.peek() == Token::Newline {
+            self.next();
+        }
+    }
+
+    /// Consumes the reserved word, or reports it missing and carries on as if
+    /// it were there.
+    fn expect_reserved(&mut self, word: &str) {
+        if self.peek_is_reserved(word) {
+            self.next();
+        } else {
+            let offset = self.peek_offset();
+            let found = describe(self.peek());
+            self.error(format!("expected `{word}` but found {found}"), offset);
+        }
+    }
+
+    fn expect_op(&mut self, op: Op) {
+        if *self.peek() == Token::Op(op) {
+            self.next();
+        } else {
+            let offset = self.peek_offset();
+            let found = describe(self.peek());
+            self.error(
+                format!("expected `{}` but found {found}", op.as_str()),
+                offset,
+            );
+        }
+    }
+
+    /// Reads and-or lists up to the end of input, a closing reserved word, a
+    /// `)` or a case terminator, none of which it consumes.
+    fn parse_list(&mut self) -> List {
+        let mut items = Vec::new();
+        loop {
+            self.skip_newlines();
+            match self.peek() {
+                Token::Eof
+                | Token::Op(Op::RParen | Op::DoubleSemi | Op::SemiAmp | Op::DoubleSemiAmp) => break,
+                Token::Op(Op::Semi | Op::Amp) => {
+                    let (token, offset) = self.next();
+                    self.error(format!("unexpected {}", describe(&token)), offset);
+                    continue;
+                }
+                token if is_closer(token) => break,
+                _ => {}
+            }
+
+            let and_or = self.parse_and_or();
+            let background = match self.peek() {
+                Token::Op(Op::Semi) => {
+                    self.next();
+                    false
+                }
+                Token::Op(Op::Amp) => {
+                    self.next();
+                    true
+                }
+                token @ (Token::Word(_) | Token::Op(Op::LParen) | Token::Arithmetic(_))
+                    if !is_closer(token) =>
+                {
+                    let offset = self.peek_offset();
+                    let found = describe(self.peek());
+                    self.error(format!("unexpected {found}"), offset);
+                    self.next();
+                    false
+                }
+                _ => false,
+            };
+            items.push(ListItem { and_or, background });
+        }
+        List { items }
+    }
+
+    fn parse_and_or(&mut self) -> AndOr {
+        let first = self.parse_pipeline();
+        let mut rest = Vec::new();
+        loop {
+            let op = match self.peek() {
+                Token::Op(Op::AndIf) => LogicalOp::And,
+                Token::Op(Op::OrIf) => LogicalOp::Or,
+                _ => break,
+            };
+            self.next();
+            self.skip_newlines();
+            rest.push((op, self.parse_pipeline()));
+        }
+        AndOr { first, rest }
+    }
+
+    fn parse_pipeline(&mut self) -> Pipeline {
+        let negated = self.peek_is_reserved("!");
+        if negated {
+            self.next();
+        }
+
+        let mut commands = vec![self.parse_command()];
+        while matches!(self.peek(), Token::Op(Op::Pipe | Op::PipeAll)) {
+            self.next();
+            self.skip_newlines();
+            commands.push(self.parse_command());
+        }
+        Pipeline { negated, commands }
+    }
+
+    fn parse_command(&mut self) -> Command {
+        if self.lexer.depth >= MAX_DEPTH {
+            return self.skip_too_deep();
+        }
+        self.lexer.depth += 1;
+        let command = self.parse_command_within_depth();
+        self.lexer.depth -= 1;
+        command
+    }
+
+    /// Reports nesting past [`MAX_DEPTH`] and drops the rest of the input,
+    /// leaving the enclosing constructs to report what they are missing.
+    fn skip_too_deep(&mut self) -> Command {
+        let offset = self.peek_offset();
+        self.error("commands nested too deeply".to_string(), offset);
+        while *self.peek() != Token::Eof {
+            self.next();
+        }
+        Command::Simple(SimpleCommand::default())
+    }
+
+    fn parse_command_within_depth(&mut self) -> Command {
+        let kind = match self.peek().clone() {
+            Token::Word(word) => match word.raw.as_str() {
+                "{" => {
+                    self.next();
+                    let body = self.parse_list();
+                    self.expect_reserved("}");
+                    Compound::Group(body)
+                }
+                "if" => self.parse_if(),
+                "while" | "until" => self.parse_loop(),
+                "for" | "select" => self.parse_for(),
+                "case" => self.parse_case(),
+                "[[" => self.parse_test(),
+                "function" => return self.parse_function_keyword(),
+                _ if self.starts_function() => return self.parse_function(),
+                _ => return Command::Simple(self.parse_simple()),
+            },
+            Token::Op(Op::LParen) => {
+                self.next();
+                let body = self.parse_list();
+                self.expect_op(Op::RParen);
+                Compound::Subshell(body)
+            }
+            Token::Arithmetic(expression) => {
+                self.next();
+                Compound::Arithmetic(expression)
+            }
+            Token::IoNumber(_) | Token::Op(Op::Redirect(_)) => {
+                return Command::Simple(self.parse_simple());
+            }
+            token => {
+                let offset = self.peek_offset();
+                self.error(
+                    format!("expected a command but found {}", describe(&token)),
+                    offset,
+                );
+                return Command::Simple(SimpleCommand::default());
+            }
+        };
+
+        let mut redirects = Vec::new();
+        while let Some(redirect) = self.parse_redirect() {
+            redirects.push(redirect);
+        }
+        Command::Compound(CompoundCommand { kind, redirects })
+    }
+
+    fn parse_simple(&mut self) -> SimpleCommand {
+        let mut command = SimpleCommand::default();
+        loop {
+            if let Some(redirect) = self.parse_redirect() {
+                command.redirects.push(redirect);
+                continue;
+            }
+            let Token::Word(_) = self.peek() else {
+                break;
+            };
+            let Token::Word(word) = self.next().0 else {
+                unreachable!("just peeked a word");
+            };
+            if command.words.is_empty() && is_assignment(&word.raw) {
+                command.assignments.push(word);
+            } else {
+                command.words.push(word);
+            }
+        }
+        command
+    }
+
+    /// A redirection at the cursor, if there is one.
+    fn parse_redirect(&mut self) -> Option<Redirect> {
+        let fd = match self.peek() {
+            Token::IoNumber(fd) => Some(*fd),
+            Token::Op(Op::Redirect(_)) => None,
+            _ => return None,
+        };
+        if fd.is_some() {
+            self.next();
+        }
+        let (token, offset) = self.next();
+        let Token::Op(Op::Redirect(op)) = token else {
+            self.error(
+                format!("expected a redirection but found {}", describe(&token)),
+                offset,
+            );
+            return None;
+        };
+
+        let target = match self.peek() {
+            Token::Word(_) => match self.next().0 {
+                Token::Word(word) => word,
+                _ => unreachable!("just peeked a word"),
+            },
+            token => {
+                let found = describe(token);
+                let offset = self.peek_offset();
+                self.error(
+                    format!(
+                        "expected a target after `{}` but found {found}",
+                        op.as_str()
+                    ),
+                    offset,
+                );
+                Word::literal("")
+            }
+        };
+
+        let heredoc = match op {
+            RedirectOp::Heredoc | RedirectOp::HeredocStripTabs => Some(Heredoc {
+                body: self
+                    .lexer
+                    .read_heredoc(&target.text, op == RedirectOp::HeredocStripTabs),
+                expands: !target.is_quoted(),
+            }),
+            _ => None,
+        };
+        Some(Redirect {
+            fd,
+            op,
+            target,
+            heredoc,
+        })
+    }
+
+    fn parse_if(&mut self) -> Compound {
+        self.next();
+        let mut branches = Vec::new();
+        loop {
+            let condition = self.parse_list();
+            self.expect_reserved("then");
+            let body = self.parse_list();
+            branches.push((condition, body));
+            if self.peek_is_reserved("elif") {
+                self.next();
+            } else {
+                break;
+            }
+        }
+
+        let otherwise = if self.peek_is_reserved("else") {
+            self.next();
+            Some(self.parse_list())
+        } else {
+            None
+        };
+        self.expect_reserved("fi");
+        Compound::If {
+            branches,
+            otherwise,
+        }
+    }
+
+    fn parse_loop(&mut self) -> Compound {
+        let (Token::Word(keyword), _) = self.next() else {
+            unreachable!("called on `while` or `until`");
+        };
+        let condition = self.parse_list();
+        let body = self.parse_do_group();
+        if keyword.raw == "until" {
+            Compound::Until { condition, body }
+        } else {
+            Compound::While { condition, body }
+        }
+    }
+
+    fn parse_do_group(&mut self) -> List {
+        self.expect_reserved("do");
+        let body = self.parse_list();
+        self.expect_reserved("done");
+        body
+    }
+
+    fn parse_for(&mut self) -> Compound {
+        let (Token::Word(keyword), _) = self.next() else {
+            unreachable!("called on `for` or `select`");
+        };
+        let select = keyword.raw == "select";
+
+        if !select {
+            if let Token::Arithmetic(header) = self.peek().clone() {
+                self.next();
+                self.skip_separator();
+                let body = self.parse_do_group();
+                return Compound::ArithmeticFor { header, body };
+            }
+        }
+
+        let variable = match self.next() {
+            (Token::Word(word), _) => word.text,
+            (token, offset) => {
+                self.error(
+                    format!("expected a loop variable but found {}", describe(&token)),
+                    offset,
+                );
+                String::new()
+            }
+        };
+
+        self.skip_newlines();
+        let words = if self.peek_is_reserved("in") {
+            self.next();
+            let mut words = Vec::new();
+            while let Token::Word(_) = self.peek() {
+                if let (Token::Word(word), _) = self.next() {
+                    words.push(word);
+                }
+            }
+            Some(words)
+        } else {
+            None
+        };
+        self.skip_separator();
+        let body = self.parse_do_group();
+
+        if select {
+            Compound::Select {
+                variable,
+                words,
+                body,
+            }
+        } else {
+            Compound::For {
+                variable,
+                words,
+                body,
+            }
+        }
+    }
+
+    /// An optional `;` followed by any number of newlines.
+    fn skip_separator(&mut self) {
+        if *self.peek() == Token::Op(Op::Semi) {
+            self.next();
+        }
+        self.skip_newlines();
+    }
+
+    fn parse_case(&mut self) -> Compound {
+        self.next();
+        let subject = match self.next() {
+            (Token::Word(word), _) => word,
+            (token, offset) => {
+                self.error(
+                    format!(
+                        "expected a word after `case` but found {}",
+                        describe(&token)
+                    ),
+                    offset,
+                );
+                Word::literal("")
+            }
+        };
+        self.skip_newlines();
+        self.expect_reserved("in");
+
+        let mut arms = Vec::new();
+        loop {
+            self.skip_newlines();
+            if self.peek_is_reserved("esac") {
+                self.next();
+                break;
+            }
+            if *self.peek() == Token::Eof {
+                let offset = self.peek_offset();
+                self.error(
+                    "expected `esac` but found the end of input".to_string(),
+                    offset,
+                );
+                break;
+            }
+
+            if *self.peek() == Token::Op(Op::LParen) {
+                self.next();
+            }
+            let mut patterns = Vec::new();
+            while let Token::Word(_) = self.peek() {
+                if let (Token::Word(word), _) = self.next() {
+                    patterns.push(word);
+                }
+                if *self.peek() != Token::Op(Op::Pipe) {
+                    break;
+                }
+                self.next();
+            }
+            if patterns.is_empty() {
+                let (token, offset) = self.next();
+                self.error(
+                    format!("expected a case pattern but found {}", describe(&token)),
+                    offset,
+                );
+                continue;
+            }
+            self.expect_op(Op::RParen);
+
+            let body = self.parse_list();
+            arms.push(CaseArm { patterns, body });
+            match self.peek() {
+                Token::Op(Op::DoubleSemi | Op::SemiAmp | Op::DoubleSemiAmp) => {
+                    self.next();
+                }
+                token if is_reserved(token, "esac") => {}
+                _ => {
+                    let offset = self.peek_offset();
+                    let found = describe(self.peek());
+                    self.error(format!("expected `;;` but found {found}"), offset);
+                    break;
+                }
+            }
+        }
+
+        Compound::Case { subject, arms }
+    }
+
+    /// `[[ … ]]`, keeping `&&`, `<` and the like as plain words.
+    fn parse_test(&mut self) -> Compound {
+        self.next();
+        let mut words = Vec::new();
+        loop {
+            match self.next() {
+                (Token::Word(word), _) if word.raw == "]]" => break,
+                (Token::Word(word), _) => words.push(word),
+                (Token::Op(op), _) => words.push(Word::literal(op.as_str())),
+                (Token::IoNumber(fd), _) => words.push(Word::literal(&fd.to_string())),
+                (Token::Arithmetic(expression), _) => {
+                    words.push(Word::literal(&format!("(({expression}))")));
+                }
+                (Token::Newline, _) => {}
+                (Token::Eof, offset) => {
+                    self.error(
+                        "expected `]]` but found the end of input".to_string(),
+                        offset,
+                    );
+                    break;
+                }
+            }
+        }
+        Compound::Test(words)
+    }
+
+    fn starts_function(&mut self) -> bool {
+        matches!(self.peek_nth(1), Token::Op(Op::LParen))
+            && matches!(self.peek_nth(2), Token::Op(Op::RParen))
+    }
+
+    /// `name() body`
+    fn parse_function(&mut self) -> Command {
+        let (Token::Word(name), _) = self.next() else {
+            unreachable!("called on a word");
+        };
+        self.next();
+        self.next();
+        self.function_body(name.text)
+    }
+
+    /// `function name [()] body`
+    fn parse_function_keyword(&mut self) -> Command {
+        self.next();
+        let name = match self.next() {
+            (Token::Word(word), _) => word.text,
+            (token, offset) => {
+                self.error(
+                    format!("expected a function name but found {}", describe(&token)),
+                    offset,
+                );
+                String::new()
+            }
+        };
+        if *self.peek() == Token::Op(Op::LParen) && *self.peek_nth(1) == Token::Op(Op::RParen) {
+            self.next();
+            self.next();
+        }
+        self.function_body(name)
+    }
+
+    fn function_body(&mut self, name: String) -> Command {
+        self.skip_newlines();
+        Command::Function(FunctionDef {
+            name,
+            body: Box::new(self.parse_command()),
+        })
+    }
+}
+
+fn is_reserved(token: &Token, word: &str) -> bool {
+    matches!(token, Token::Word(w) if w.raw == word)
+}
+
+fn is_closer(token: &Token) -> bool {
+    matches!(token, Token::Word(w) if CLOSERS.contains(&w.raw.as_str()))
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Word(word) => format!("`{}`", word.raw),
+        Token::IoNumber(fd) => format!("`{fd}`"),
+        Token::Op(op) => format!("`{}`", op.as_str()),
+        Token::Newline => "a newline".to_string(),
+        Token::Arithmetic(expression) => format!("`(({expression}))`"),
+        Token::Eof => "the end of input".to_string(),
+    }
+}
+
+fn is_assignment(raw: &str) -> bool {
+    match raw.split_once('=') {
+        Some((name, _)) => {
+            let name = name.strip_suffix('+').unwrap_or(name);
+            !name.is_empty()
+                && !name.starts_with(|c: char| c.is_ascii_digit())
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(source: &str) -> Vec<String> {
+        parse(source, 0)
+            .1
+            .into_iter()
+            .map(|error| error.message)
+            .collect()
+    }
+
+    #[test]
+    fn valid_scripts_have_no_errors() {
+        for source in [
+            "",
+            "ls -la",
+            "a && b || c; d & e | f",
+            "if a; then b; elif c; then d; else e; fi",
+            "while read -r line; do echo \"$line\"; done < input.txt",
+            "until false\ndo\n  sleep 1\ndone",
+            "for f in *.txt; do wc -l \"$f\"; done",
+            "for ((i = 0; i < 3; i++)); do echo $i; done",
+            "case $x in a|b) echo ab ;; *) echo other ;; esac",
+            "greet() { echo hi; }; greet",
+            "function greet { echo hi; }",
+            "[[ -f a && $b < c ]] && echo yes",
+            "((count++))",
+            "( cd src && make ) > build.log 2>&1",
+            "cat <<EOF\nhello\nEOF",
+            "files=(a b c); echo \"${files[@]}\"",
+            "! grep -q x file",
+        ] {
+            assert_eq!(messages(source), Vec::<String>::new(), "{source}");
+        }
+    }
+
+    #[test]
+    fn missing_closers_are_reported() {
+        assert_eq!(
+            messages("if true; then echo"),
+            vec!["expected `fi` but found the end of input"]
+        );
+        assert_eq!(
+            messages("while true; do echo"),
+            vec!["expected `done` but found the end of input"]
+        );
+        assert_eq!(
+            messages("(echo"),
+            vec!["expected `)` but found the end of input"]
+        );
+        assert_eq!(
+            messages("{ echo; "),
+            vec!["expected `}` but found the end of input"]
+        );
+    }
+
+    #[test]
+    fn stray_tokens_are_reported_and_skipped() {
+        assert_eq!(messages("echo a )"), vec!["unexpected `)`"]);
+        assert_eq!(messages("fi"), vec!["unexpected `fi`"]);
+        assert_eq!(messages("; ls"), vec!["unexpected `;`"]);
+        assert_eq!(
+            messages("ls |"),
+            vec!["expected a command but found the end of input"]
+        );
+        assert_eq!(
+            messages("cat >"),
+            vec!["expected a target after `>` but found the end of input"]
+        );
+    }
+
+    #[test]
+    fn errors_in_substitutions_carry_their_offset() {
+        let (_, errors) = parse("echo $(if x)", 0);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(
+            errors[0].message,
+            "expected `then` but found the end of input"
+        );
+        assert_eq!(errors[0].offset, 5 + 4);
+    }
+
+    #[test]
+    fn assignments_need_a_valid_name() {
+        assert!(is_assignment("A=1"));
+        assert!(is_assignment("PATH+=:/opt/bin"));
+        assert!(!is_assignment("1A=1"));
+        assert!(!is_assignment("--opt=1"));
+        assert!(!is_assignment("plain"));
+    }
+}
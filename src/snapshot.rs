@@ -0,0 +1,100 @@
+use crate::impact::Impact;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::process::Command;
+
+/// Enough file names for the provider to reverse a move or rename without
+/// bloating every history line.
+const MAX_RECORDED_FILES: usize = 200;
+
+/// State captured just before `tt` runs a command, so `tt undo` can work out
+/// how to reverse it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub cwd: PathBuf,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_head: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_branch: Option<String>,
+    /// Whether the work tree had uncommitted changes.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub git_dirty: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<Targets>,
+}
+
+/// The files one destructive part of the command was about to touch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Targets {
+    pub action: String,
+    pub files: Vec<PathBuf>,
+    /// How many files there were before `files` was cut down.
+    pub total: usize,
+    /// Set when the impact listing itself stopped early, so `total` is only
+    /// a lower bound.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+}
+
+/// Captures the state in the current directory. `impacts` is what
+/// [`crate::impact::assess`] found for the command, so it is not listed twice.
+pub async fn capture(impacts: &[Impact]) -> Snapshot {
+    match std::env::current_dir() {
+        Ok(dir) => capture_in(&dir, impacts).await,
+        Err(_) => Snapshot::default(),
+    }
+}
+
+pub async fn capture_in(dir: &Path, impacts: &[Impact]) -> Snapshot {
+    let git_head = git(dir, &["rev-parse", "HEAD"]).await;
+    let (git_branch, git_dirty) = if git_head.is_some() {
+        let branch = git(dir, &["symbolic-ref", "--short", "-q", "HEAD"]).await;
+        let status = git(dir, &["status", "--porcelain", "--untracked-files=no"]).await;
+        (branch, status.is_some())
+    } else {
+        (None, false)
+    };
+
+    let targets = impacts
+        .iter()
+        .map(|impact| Targets {
+            action: impact.action.clone(),
+            files: impact
+                .files
+                .iter()
+                .take(MAX_RECORDED_FILES)
+                .cloned()
+                .collect(),
+            total: impact.files.len(),
+            truncated: impact.truncated,
+        })
+        .collect();
+
+    Snapshot {
+        cwd: dir.to_path_buf(),
+        git_head,
+        git_branch,
+        git_dirty,
+        targets,
+    }
+}
+
+/// Trimmed stdout of a successful git command, or `None` when it failed or
+/// printed nothing.
+async fn git(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .output()
+        .await
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
@@ -0,0 +1,341 @@
+use crate::binaries;
+use crate::shell;
+use std::ffi::OsStr;
+
+/// Subcommands of tools whose typos are common enough to fix locally. Plugins
+/// installed as `git-<name>` or `cargo-<name>` on `$PATH` count as known too.
+const SUBCOMMANDS: &[(&str, &[&str])] = &[
+    (
+        "git",
+        &[
+            "add",
+            "am",
+            "apply",
+            "archive",
+            "bisect",
+            "blame",
+            "branch",
+            "bundle",
+            "checkout",
+            "cherry-pick",
+            "clean",
+            "clone",
+            "commit",
+            "config",
+            "describe",
+            "diff",
+            "fetch",
+            "format-patch",
+            "gc",
+            "grep",
+            "init",
+            "log",
+            "merge",
+            "mv",
+            "notes",
+            "pull",
+            "push",
+            "rebase",
+            "reflog",
+            "remote",
+            "reset",
+            "restore",
+            "revert",
+            "rm",
+            "shortlog",
+            "show",
+            "sparse-checkout",
+            "stash",
+            "status",
+            "submodule",
+            "switch",
+            "tag",
+            "worktree",
+        ],
+    ),
+    (
+        "cargo",
+        &[
+            "add",
+            "bench",
+            "build",
+            "check",
+            "clean",
+            "clippy",
+            "doc",
+            "fetch",
+            "fix",
+            "fmt",
+            "init",
+            "install",
+            "login",
+            "metadata",
+            "new",
+            "package",
+            "publish",
+            "remove",
+            "run",
+            "rustc",
+            "rustdoc",
+            "search",
+            "test",
+            "tree",
+            "uninstall",
+            "update",
+            "vendor",
+            "version",
+            "yank",
+        ],
+    ),
+    (
+        "docker",
+        &[
+            "attach",
+            "build",
+            "buildx",
+            "commit",
+            "compose",
+            "container",
+            "context",
+            "cp",
+            "create",
+            "diff",
+            "events",
+            "exec",
+            "export",
+            "history",
+            "image",
+            "images",
+            "import",
+            "info",
+            "inspect",
+            "kill",
+            "load",
+            "login",
+            "logout",
+            "logs",
+            "network",
+            "pause",
+            "port",
+            "ps",
+            "pull",
+            "push",
+            "rename",
+            "restart",
+            "rm",
+            "rmi",
+            "run",
+            "save",
+            "search",
+            "start",
+            "stats",
+            "stop",
+            "system",
+            "tag",
+            "top",
+            "unpause",
+            "update",
+            "version",
+            "volume",
+            "wait",
+        ],
+    ),
+];
+
+/// A command rewritten with its misspelled names replaced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Correction {
+    pub command: String,
+    pub fixes: Vec<Fix>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fix {
+    pub typo: String,
+    pub replacement: String,
+}
+
+pub fn correct(command: &str) -> Option<Correction> {
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    correct_in(command, &path)
+}
+
+/// Fixes a program name that is not on `path`, then an unknown `git`, `cargo`
+/// or `docker` subcommand, when each has exactly one close match. `None` when
+/// nothing needed fixing or no match was confident enough.
+pub fn correct_in(command: &str, path: &OsStr) -> Option<Correction> {
+    let mut corrected = command.to_string();
+    let mut fixes = Vec::new();
+
+    if let Some(missing) = binaries::missing_programs_in(command, path)
+        .into_iter()
+        .next()
+    {
+        let executables = binaries::path_executables(path);
+        // With no close program the subcommand may still be worth fixing.
+        if let Some(replacement) = closest(&missing, executables.iter().map(String::as_str)) {
+            corrected = replace_word(&corrected, 0, &missing, replacement)?.0;
+            fixes.push(Fix {
+                typo: missing,
+                replacement: replacement.to_string(),
+            });
+        }
+    }
+
+    if let Some((fix, rewritten)) = correct_subcommand(&corrected, path) {
+        corrected = rewritten;
+        fixes.push(fix);
+    }
+
+    if fixes.is_empty() {
+        return None;
+    }
+    Some(Correction {
+        command: corrected,
+        fixes,
+    })
+}
+
+fn correct_subcommand(command: &str, path: &OsStr) -> Option<(Fix, String)> {
+    let list = shell::parse_partial(command);
+    for stage in list.stages() {
+        let (_, args) = shell::unwrap_wrappers(&stage.command.words);
+        let Some((program, subcommand)) = args.first().zip(args.get(1)) else {
+            continue;
+        };
+        let Some((_, known)) = SUBCOMMANDS
+            .iter()
+            .find(|(tool, _)| *tool == shell::program_name(&program.text))
+        else {
+            continue;
+        };
+        let typo = subcommand.text.as_str();
+        if subcommand.raw != typo || typo.starts_with('-') || known.contains(&typo) {
+            continue;
+        }
+        let plugin = format!("{}-{typo}", shell::program_name(&program.text));
+        if binaries::is_on_path(&plugin, path) {
+            continue;
+        }
+
+        let replacement = closest(typo, known.iter().copied())?;
+        let (_, program_end) = replace_word(command, 0, &program.raw, &program.raw)?;
+        let (rewritten, _) = replace_word(command, program_end, typo, replacement)?;
+        let fix = Fix {
+            typo: typo.to_string(),
+            replacement: replacement.to_string(),
+        };
+        return Some((fix, rewritten));
+    }
+    None
+}
+
+/// The one candidate within a small edit distance of `word`. Ties are not
+/// confident, so they return `None`.
+pub fn closest<'a>(word: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let limit = match word.chars().count() {
+        0..=2 => return None,
+        3..=4 => 1,
+        _ => 2,
+    };
+
+    let mut best: Option<(usize, &str)> = None;
+    let mut tied = false;
+    for candidate in candidates {
+        if candidate == word {
+            return None;
+        }
+        let distance = edit_distance(word, candidate);
+        if distance > limit {
+            continue;
+        }
+        match best {
+            Some((best_distance, _)) if distance > best_distance => {}
+            Some((best_distance, _)) if distance == best_distance => tied = true,
+            _ => {
+                best = Some((distance, candidate));
+                tied = false;
+            }
+        }
+    }
+
+    if tied {
+        return None;
+    }
+    best.map(|(_, candidate)| candidate)
+}
+
+/// Levenshtein distance where swapping two adjacent characters counts as one
+/// edit, since `gti` for `git` is the most common slip.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = distance;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+/// Replaces the first whole-word `word` at or after byte `from`, returning
+/// the new source and the byte where the replacement ends.
+fn replace_word(
+    source: &str,
+    from: usize,
+    word: &str,
+    replacement: &str,
+) -> Option<(String, usize)> {
+    let is_boundary = |c: Option<char>| {
+        c.is_none_or(|c| {
+            c.is_whitespace() || matches!(c, ';' | '|' | '&' | '(' | ')' | '{' | '}' | '`')
+        })
+    };
+
+    let mut offset = from;
+    while let Some(found) = source.get(offset..)?.find(word) {
+        let start = offset + found;
+        let end = start + word.len();
+        if is_boundary(source[..start].chars().next_back())
+            && is_boundary(source[end..].chars().next())
+        {
+            let rewritten = format!("{}{replacement}{}", &source[..start], &source[end..]);
+            return Some((rewritten, start + replacement.len()));
+        }
+        offset = end;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replacement_only_touches_whole_words() {
+        assert_eq!(
+            replace_word("gti status && echo gti-done", 0, "gti", "git"),
+            Some(("git status && echo gti-done".to_string(), 3))
+        );
+        assert_eq!(
+            replace_word("sudo apt-get instal x", 5, "instal", "install"),
+            Some(("sudo apt-get install x".to_string(), 20))
+        );
+        assert_eq!(replace_word("xgti", 0, "gti", "git"), None);
+    }
+}
@@ -0,0 +1,72 @@
+use std::ffi::OsString;
+use std::fs;
+use tempfile::TempDir;
+use tt::binaries::{invoked_programs, is_on_path, missing_programs_in};
+
+fn fake_path(programs: &[&str]) -> (TempDir, OsString) {
+    let dir = TempDir::new().expect("temp dir");
+    for program in programs {
+        let path = dir.path().join(program);
+        fs::write(&path, "#!/bin/sh\n").expect("write program");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).expect("chmod");
+        }
+    }
+    let path = dir.path().as_os_str().to_owned();
+    (dir, path)
+}
+
+#[test]
+fn programs_across_pipelines_wrappers_and_substitutions() {
+    assert_eq!(
+        invoked_programs("sudo fd -e log | xargs -0 rg TODO && echo \"$(gdate +%s)\""),
+        vec!["sudo", "fd", "xargs", "rg", "gdate"]
+    );
+}
+
+#[test]
+fn builtins_keywords_and_dynamic_names_are_skipped() {
+    let programs = invoked_programs(
+        "for f in *.txt; do cd /tmp && $EDITOR \"$f\"; done; if true; then ./build.sh; fi",
+    );
+    assert!(programs.is_empty(), "unexpected: {programs:?}");
+}
+
+#[test]
+fn nested_scripts_and_find_exec_are_inspected() {
+    assert_eq!(
+        invoked_programs(r"find . -name '*.png' -exec optipng {} \; ; bash -c 'pbcopy < x'"),
+        vec!["find", "optipng", "bash", "pbcopy"]
+    );
+}
+
+#[test]
+fn inline_function_definitions_are_not_programs() {
+    assert_eq!(
+        invoked_programs("greet() { printf 'hi\\n'; }; greet; jq ."),
+        vec!["jq"]
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn missing_programs_are_checked_against_path() {
+    let (_dir, path) = fake_path(&["find", "xargs"]);
+    assert!(is_on_path("find", &path));
+    assert!(!is_on_path("fd", &path));
+    assert_eq!(
+        missing_programs_in("fd -e rs | xargs gsed -i 's/a/b/'", &path),
+        vec!["fd", "gsed"]
+    );
+    assert!(missing_programs_in("find . -print0 | xargs -0 echo", &path).is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn non_executable_files_do_not_count() {
+    let dir = TempDir::new().expect("temp dir");
+    fs::write(dir.path().join("rg"), "").expect("write");
+    assert!(!is_on_path("rg", dir.path().as_os_str()));
+}
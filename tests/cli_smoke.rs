@@ -21,3 +21,162 @@ fn config_command_reports_missing_setup() {
         .success()
         .stdout(predicate::str::contains("No configuration found"));
 }
+
+#[test]
+fn init_prints_shell_widgets() {
+    let env = TestEnv::new();
+    env.tt_cmd()
+        .args(["init", "bash"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("bind -x").and(predicate::str::contains("--command-only")),
+        );
+    env.tt_cmd()
+        .args(["init", "zsh"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("bindkey '^G' __tt_widget")
+                .and(predicate::str::contains("TT_LAST_COMMAND")),
+        );
+    env.tt_cmd()
+        .args(["init", "fish"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("commandline -r"));
+}
+
+#[test]
+fn init_rejects_unknown_shells() {
+    let env = TestEnv::new();
+    env.tt_cmd().args(["init", "tcsh"]).assert().failure();
+}
+
+#[test]
+fn command_only_conflicts_with_run() {
+    let env = TestEnv::new();
+    env.tt_cmd()
+        .args(["--run", "--command-only", "list files"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn retry_requires_run() {
+    let env = TestEnv::new();
+    env.tt_cmd()
+        .args(["--retry", "2", "list files"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--run"));
+}
+
+#[test]
+fn summarize_requires_run() {
+    let env = TestEnv::new();
+    env.tt_cmd()
+        .args(["--summarize", "which directory is biggest"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--run"));
+}
+
+#[test]
+fn unknown_options_after_a_subcommand_are_still_errors() {
+    let env = TestEnv::new();
+    env.tt_cmd()
+        .args(["undo", "--bogus"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unexpected argument '--bogus'"));
+}
+
+#[test]
+fn options_before_a_subcommand_are_rejected() {
+    let env = TestEnv::new();
+    env.tt_cmd()
+        .args(["--run", "fix"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("go after the subcommand"));
+}
+
+#[test]
+fn timeout_requires_run_and_a_duration() {
+    let env = TestEnv::new();
+    env.tt_cmd()
+        .args(["--timeout", "30s", "list files"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--run"));
+    env.tt_cmd()
+        .args(["--run", "--timeout", "soon", "list files"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid duration"));
+}
+
+#[test]
+fn explain_accepts_unquoted_flags_and_requires_setup() {
+    let env = TestEnv::new();
+    env.tt_cmd().arg("explain").assert().failure();
+    env.tt_cmd()
+        .args(["explain", "ls", "-la"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No configuration found"));
+}
+
+#[test]
+fn translate_requires_a_target_shell() {
+    let env = TestEnv::new();
+    env.tt_cmd()
+        .args(["translate", "ls", "-la"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--to"));
+    env.tt_cmd()
+        .args(["translate", "--to", "tcsh", "ls"])
+        .assert()
+        .failure();
+    env.tt_cmd()
+        .args(["translate", "--to", "bash", "ls"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("already bash"));
+    env.tt_cmd()
+        .args(["translate", "--to", "pwsh", "echo", "$HOME"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No configuration found"));
+}
+
+#[test]
+fn fix_needs_a_previous_command() {
+    let env = TestEnv::new();
+    env.tt_cmd()
+        .arg("fix")
+        .env_remove("TT_LAST_COMMAND")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No previous command found"));
+    env.tt_cmd()
+        .arg("fix")
+        .env("TT_LAST_COMMAND", "git comit -m wip")
+        .env("TT_LAST_STATUS", "1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No configuration found"));
+}
+
+#[test]
+fn undo_needs_a_recorded_command() {
+    let env = TestEnv::new();
+    env.tt_cmd()
+        .arg("undo")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No command to undo yet"));
+}
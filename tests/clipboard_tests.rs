@@ -0,0 +1,42 @@
+use tt::clipboard::{Multiplexer, base64_encode, osc52_sequence};
+
+#[test]
+fn base64_matches_rfc_vectors() {
+    assert_eq!(base64_encode(b""), "");
+    assert_eq!(base64_encode(b"f"), "Zg==");
+    assert_eq!(base64_encode(b"fo"), "Zm8=");
+    assert_eq!(base64_encode(b"foo"), "Zm9v");
+    assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+}
+
+#[test]
+fn plain_terminal_gets_bare_osc52() {
+    assert_eq!(
+        osc52_sequence("ls -la", Multiplexer::None),
+        "\x1b]52;c;bHMgLWxh\x07"
+    );
+}
+
+#[test]
+fn tmux_wraps_and_doubles_escapes() {
+    assert_eq!(
+        osc52_sequence("ls -la", Multiplexer::Tmux),
+        "\x1bPtmux;\x1b\x1b]52;c;bHMgLWxh\x07\x1b\\"
+    );
+}
+
+#[test]
+fn screen_splits_long_payloads_into_dcs_chunks() {
+    let long = "x".repeat(2000);
+    let sequence = osc52_sequence(&long, Multiplexer::Screen);
+    let chunks: Vec<&str> = sequence.split("\x1bP").skip(1).collect();
+    assert!(chunks.len() > 1);
+    assert!(chunks.iter().all(|chunk| chunk.ends_with("\x1b\\")));
+    assert!(chunks.iter().all(|chunk| chunk.len() <= 768 + 2));
+
+    let rejoined: String = chunks
+        .iter()
+        .map(|chunk| chunk.trim_end_matches("\x1b\\"))
+        .collect();
+    assert_eq!(rejoined, osc52_sequence(&long, Multiplexer::None));
+}
@@ -49,6 +49,9 @@ fn base_config(provider: ProviderKind) -> Config {
         show_header: true,
         show_model_in_header: true,
         system_prompt_style: SystemPromptStyle::Command,
+        copy_to_clipboard: false,
+        replace_missing_tools: false,
+        offline_typo_fix: false,
     }
 }
 
@@ -116,6 +119,9 @@ fn config_defaults_enable_header_and_command_prompt() {
     assert!(cfg.show_header);
     assert!(cfg.show_model_in_header);
     assert_eq!(cfg.system_prompt_style, SystemPromptStyle::Command);
+    assert!(!cfg.copy_to_clipboard);
+    assert!(!cfg.replace_missing_tools);
+    assert!(!cfg.offline_typo_fix);
 }
 
 #[test]
@@ -127,10 +133,12 @@ fn config_persists_display_preferences() {
     cfg.show_header = false;
     cfg.show_model_in_header = false;
     cfg.system_prompt_style = SystemPromptStyle::Exploration;
+    cfg.copy_to_clipboard = true;
     cfg.save().expect("save config");
 
     let loaded = Config::load().expect("load config");
     assert!(!loaded.show_header);
     assert!(!loaded.show_model_in_header);
     assert_eq!(loaded.system_prompt_style, SystemPromptStyle::Exploration);
+    assert!(loaded.copy_to_clipboard);
 }
@@ -0,0 +1,200 @@
+use std::process::{Command, ExitCode};
+use std::time::{Duration, Instant};
+use tt::exec::{
+    Capture, ShellKind, SyntaxCheck, TIMEOUT_EXIT_CODE, check_syntax_with, exit_code,
+    parse_duration, run_in_shell_with_timeout,
+};
+
+#[test]
+fn shell_kind_is_detected_from_program_path() {
+    assert_eq!(ShellKind::from_program("/bin/zsh"), Some(ShellKind::Zsh));
+    assert_eq!(
+        ShellKind::from_program("/usr/local/bin/fish"),
+        Some(ShellKind::Fish)
+    );
+    assert_eq!(
+        ShellKind::from_program(r"C:\Windows\System32\cmd.exe"),
+        Some(ShellKind::Cmd)
+    );
+    assert_eq!(ShellKind::from_program("/usr/bin/xonsh"), None);
+}
+
+#[cfg(unix)]
+#[test]
+fn exit_code_passes_child_status_through() {
+    let status = Command::new("sh")
+        .args(["-c", "exit 3"])
+        .status()
+        .expect("run sh");
+    assert_eq!(exit_code(&status), ExitCode::from(3));
+
+    let status = Command::new("sh")
+        .args(["-c", "kill -TERM $$"])
+        .status()
+        .expect("run sh");
+    assert_eq!(exit_code(&status), ExitCode::from(128 + 15));
+}
+
+#[test]
+fn parse_only_flags_match_each_shell() {
+    assert_eq!(ShellKind::Bash.parse_only_flag(), Some("-n"));
+    assert_eq!(ShellKind::Fish.parse_only_flag(), Some("--no-execute"));
+    assert_eq!(ShellKind::Cmd.parse_only_flag(), None);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn syntax_check_reports_unbalanced_quotes() {
+    assert_eq!(
+        check_syntax_with("sh", ShellKind::Sh, "echo 'hello' | wc -c").await,
+        SyntaxCheck::Valid
+    );
+
+    let SyntaxCheck::Invalid(message) =
+        check_syntax_with("sh", ShellKind::Sh, "echo \"unterminated").await
+    else {
+        panic!("expected a syntax error");
+    };
+    assert!(!message.starts_with("sh:"), "prefix kept: {message}");
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn syntax_check_never_executes_the_command() {
+    let dir = tempfile::TempDir::new().expect("temp dir");
+    let marker = dir.path().join("ran");
+    let command = format!("touch '{}'", marker.display());
+
+    assert_eq!(
+        check_syntax_with("sh", ShellKind::Sh, &command).await,
+        SyntaxCheck::Valid
+    );
+    assert!(!marker.exists());
+}
+
+#[tokio::test]
+async fn syntax_check_is_unavailable_without_a_shell() {
+    assert_eq!(
+        check_syntax_with("tt-no-such-shell", ShellKind::Bash, "ls").await,
+        SyntaxCheck::Unavailable
+    );
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn captured_stderr_keeps_only_the_tail() {
+    let capture = Capture {
+        stdout: None,
+        stderr_tail: Some(10),
+    };
+    let run = run_in_shell_with_timeout("printf 'first\\nlast error' >&2; exit 4", capture, None)
+        .await
+        .expect("run command");
+    assert_eq!(run.status.code(), Some(4));
+    assert_eq!(run.stderr_tail, "last error");
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn captured_stdout_keeps_both_ends_in_whole_lines() {
+    let capture = Capture {
+        stdout: Some((12, 12)),
+        stderr_tail: None,
+    };
+    let run = run_in_shell_with_timeout("seq 1 1000", capture, None)
+        .await
+        .expect("run command");
+    assert!(run.status.success());
+    assert_eq!(
+        run.stdout,
+        "1\n2\n3\n4\n5\n6\n[… 3872 bytes omitted …]\n999\n1000\n"
+    );
+    assert!(run.stderr_tail.is_empty());
+
+    let run = run_in_shell_with_timeout("printf 'short\\n'; echo oops >&2", capture, None)
+        .await
+        .expect("run command");
+    assert_eq!(run.stdout, "short\n");
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn both_streams_can_be_captured_together() {
+    let capture = Capture {
+        stdout: Some((100, 100)),
+        stderr_tail: Some(100),
+    };
+    let run = run_in_shell_with_timeout("echo out; echo err >&2; exit 1", capture, None)
+        .await
+        .expect("run command");
+    assert_eq!(run.status.code(), Some(1));
+    assert_eq!(run.stdout, "out\n");
+    assert_eq!(run.stderr_tail, "err\n");
+}
+
+#[test]
+fn durations_accept_units_and_bare_seconds() {
+    assert_eq!(parse_duration("30"), Ok(Duration::from_secs(30)));
+    assert_eq!(parse_duration("30s"), Ok(Duration::from_secs(30)));
+    assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+    assert_eq!(parse_duration("1m30s"), Ok(Duration::from_secs(90)));
+    assert_eq!(parse_duration("2h"), Ok(Duration::from_secs(7200)));
+    assert!(parse_duration("0s").is_err());
+    assert!(parse_duration("0").is_err());
+    assert!(parse_duration("0m0ms").is_err());
+    assert!(parse_duration("").is_err());
+    assert!(parse_duration("9999999999999999h").is_err());
+    assert!(parse_duration("18446744073709551615s1s").is_err());
+    assert!(parse_duration("soon").is_err());
+    assert!(parse_duration("5 minutes").is_err());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn timeout_kills_the_whole_process_group() {
+    let capture = Capture {
+        stdout: Some((0, 100)),
+        stderr_tail: Some(100),
+    };
+    let started = Instant::now();
+    // The background sleep holds the pipes open, so the run only ends early
+    // if it is killed along with the shell.
+    let run = run_in_shell_with_timeout(
+        "echo started; sleep 30 & sleep 30; wait",
+        capture,
+        Some(Duration::from_millis(300)),
+    )
+    .await
+    .expect("run command");
+    assert!(started.elapsed() < Duration::from_secs(10));
+    assert_eq!(run.timed_out, Some(Duration::from_millis(300)));
+    assert_eq!(run.exit_code(), ExitCode::from(TIMEOUT_EXIT_CODE));
+    assert_eq!(run.stdout, "started\n");
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn timeout_falls_back_to_sigkill() {
+    let started = Instant::now();
+    let run = run_in_shell_with_timeout(
+        "trap '' TERM; sleep 30",
+        Capture::default(),
+        Some(Duration::from_millis(200)),
+    )
+    .await
+    .expect("run command");
+    assert!(started.elapsed() < Duration::from_secs(10));
+    assert!(run.timed_out.is_some());
+    assert!(!run.status.success());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn commands_within_the_limit_are_left_alone() {
+    let run =
+        run_in_shell_with_timeout("exit 3", Capture::default(), Some(Duration::from_secs(30)))
+            .await
+            .expect("run command");
+    assert_eq!(run.timed_out, None);
+    assert_eq!(run.exit_code(), ExitCode::from(3));
+}
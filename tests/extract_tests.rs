@@ -0,0 +1,274 @@
+use tt::extract::{CandidateKind, extract, extract_alternatives, extract_plan};
+
+#[test]
+fn claude_command_mode_inline_span() {
+    let extraction = extract("`brew upgrade`");
+    assert_eq!(extraction.primary_command(), Some("brew upgrade"));
+    assert_eq!(extraction.candidates.len(), 1);
+    assert_eq!(extraction.candidates[0].kind, CandidateKind::Inline);
+    assert!(!extraction.candidates[0].multiline);
+    assert!(extraction.explanation.is_empty());
+}
+
+#[test]
+fn claude_sidekick_prefers_multi_word_span_over_mentions() {
+    let response = "Use `du` with `-h` to keep sizes readable: `du -sh ./* | sort -h`. \
+                    Hidden entries like `.cache` are skipped.";
+    let extraction = extract(response);
+    assert_eq!(extraction.primary_command(), Some("du -sh ./* | sort -h"));
+    assert!(
+        extraction
+            .candidates
+            .iter()
+            .all(|candidate| candidate.command != "-h")
+    );
+    assert!(extraction.explanation.starts_with("Use `du`"));
+}
+
+#[test]
+fn openai_fenced_block_with_language_and_explanation() {
+    let response = "This finds Python files changed in the last day:\n\n\
+                    ```bash\n\
+                    find . -name '*.py' -mtime -1\n\
+                    ```\n";
+    let extraction = extract(response);
+    let primary = extraction.primary().expect("primary command");
+    assert_eq!(primary.command, "find . -name '*.py' -mtime -1");
+    assert_eq!(primary.language.as_deref(), Some("bash"));
+    assert_eq!(primary.kind, CandidateKind::Fenced);
+    assert_eq!(
+        extraction.explanation,
+        "This finds Python files changed in the last day:"
+    );
+}
+
+#[test]
+fn exploration_mode_picks_last_of_competing_blocks() {
+    let response = "First preview what would be removed:\n\n\
+                    ```sh\n\
+                    git clean -n -d\n\
+                    ```\n\n\
+                    Example output:\n\n\
+                    ```text\n\
+                    Would remove build/\n\
+                    ```\n\n\
+                    Then run the real thing:\n\n\
+                    ```sh\n\
+                    git clean -f -d\n\
+                    ```";
+    let extraction = extract(response);
+    let commands: Vec<&str> = extraction
+        .candidates
+        .iter()
+        .map(|candidate| candidate.command.as_str())
+        .collect();
+    assert_eq!(commands, vec!["git clean -n -d", "git clean -f -d"]);
+    assert_eq!(extraction.primary_command(), Some("git clean -f -d"));
+}
+
+#[test]
+fn heredoc_containing_a_fence_stays_in_one_block() {
+    let response = "```bash\n\
+                    cat > NOTES.md <<'EOF'\n\
+                    ```\n\
+                    fenced inside the file\n\
+                    ```\n\
+                    EOF\n\
+                    ```\n";
+    let extraction = extract(response);
+    let primary = extraction.primary().expect("primary command");
+    assert!(primary.multiline);
+    assert_eq!(
+        primary.command,
+        "cat > NOTES.md <<'EOF'\n```\nfenced inside the file\n```\nEOF"
+    );
+}
+
+#[test]
+fn every_heredoc_on_a_line_is_read_before_the_fence_can_close() {
+    let response = "```bash\n\
+                    diff /dev/fd/3 - 3<<ONE <<\"TWO\"\n\
+                    a\n\
+                    ONE\n\
+                    ```\n\
+                    TWO\n\
+                    echo \"$(cat <<END\n\
+                    ```\n\
+                    END\n\
+                    )\"\n\
+                    ```\n";
+    assert_eq!(
+        extract(response).primary_command(),
+        Some(
+            "diff /dev/fd/3 - 3<<ONE <<\"TWO\"\na\nONE\n```\nTWO\necho \"$(cat <<END\n```\nEND\n)\""
+        )
+    );
+}
+
+#[test]
+fn here_strings_do_not_open_a_heredoc() {
+    let response = "```bash\ngrep -c x <<< \"$text\"\n```\nDone.";
+    assert_eq!(
+        extract(response).primary_command(),
+        Some("grep -c x <<< \"$text\"")
+    );
+}
+
+#[test]
+fn tab_stripping_heredoc_terminates_on_indented_delimiter() {
+    let response = "```bash\nif true; then\n\tcat <<-END\n\thello\n\tEND\nfi\n```";
+    let extraction = extract(response);
+    assert_eq!(
+        extraction.primary_command(),
+        Some("if true; then\n\tcat <<-END\n\thello\n\tEND\nfi")
+    );
+}
+
+#[test]
+fn openrouter_console_transcript_drops_output_and_prompts() {
+    let response = "```console\n\
+                    $ docker ps --format '{{.Names}}' \\\n\
+                    \x20   --filter status=running\n\
+                    web-1\n\
+                    db-1\n\
+                    ```";
+    let extraction = extract(response);
+    assert_eq!(
+        extraction.primary_command(),
+        Some("docker ps --format '{{.Names}}' \\\n    --filter status=running")
+    );
+}
+
+#[test]
+fn lmstudio_reasoning_is_ignored() {
+    let response =
+        "<think>\nThe user wants `rm -rf /tmp/x`? No, list it.\n</think>\n\n`ls -la /tmp/x`";
+    let extraction = extract(response);
+    assert_eq!(extraction.primary_command(), Some("ls -la /tmp/x"));
+    assert_eq!(extraction.candidates.len(), 1);
+}
+
+#[test]
+fn unterminated_reasoning_yields_nothing() {
+    let extraction = extract("<think>maybe `ls`");
+    assert!(extraction.is_empty());
+    assert!(extraction.primary().is_none());
+}
+
+#[test]
+fn untagged_and_tilde_fences_are_commands() {
+    let extraction = extract("~~~\nuname -a\n~~~");
+    let primary = extraction.primary().expect("primary command");
+    assert_eq!(primary.command, "uname -a");
+    assert_eq!(primary.language, None);
+}
+
+#[test]
+fn non_shell_fences_are_not_candidates() {
+    let response = "Config:\n```json\n{\"a\": 1}\n```\nApply with `jq . config.json`.";
+    let extraction = extract(response);
+    assert_eq!(extraction.candidates.len(), 1);
+    assert_eq!(extraction.primary_command(), Some("jq . config.json"));
+}
+
+#[test]
+fn single_line_triple_backticks_are_inline() {
+    let extraction = extract("```ls -la```");
+    assert_eq!(extraction.primary_command(), Some("ls -la"));
+}
+
+#[test]
+fn double_backtick_span_keeps_inner_backticks() {
+    let extraction = extract("`` echo `date` ``");
+    assert_eq!(extraction.primary_command(), Some("echo `date`"));
+}
+
+#[test]
+fn truncated_stream_keeps_open_block() {
+    let extraction = extract("Run:\n```zsh\nprint -l **/*.rs");
+    assert_eq!(extraction.primary_command(), Some("print -l **/*.rs"));
+    assert_eq!(extraction.explanation, "Run:");
+}
+
+#[test]
+fn indented_fence_in_list_is_dedented() {
+    let response = "1. Install:\n   ```bash\n   npm ci\n   ```";
+    assert_eq!(extract(response).primary_command(), Some("npm ci"));
+}
+
+#[test]
+fn prose_without_code_has_no_candidates() {
+    let extraction = extract("I can't help with that request.");
+    assert!(extraction.is_empty());
+    assert_eq!(extraction.explanation, "I can't help with that request.");
+}
+
+#[test]
+fn numbered_alternatives_with_tradeoffs() {
+    let response = "1. `du -sh * | sort -h` — quick, but skips hidden files\n\
+                    2. `ncdu .` - interactive, needs ncdu installed\n\
+                    3) `find . -type f -size +100M` : lists only large files";
+    let alternatives = extract_alternatives(response);
+    let pairs: Vec<(&str, &str)> = alternatives
+        .iter()
+        .map(|alt| (alt.command.as_str(), alt.tradeoff.as_str()))
+        .collect();
+    assert_eq!(
+        pairs,
+        [
+            ("du -sh * | sort -h", "quick, but skips hidden files"),
+            ("ncdu .", "interactive, needs ncdu installed"),
+            ("find . -type f -size +100M", "lists only large files"),
+        ]
+    );
+}
+
+#[test]
+fn alternatives_with_fenced_commands_and_duplicates() {
+    let response = "<think>several options</think>\n\
+                    1. Archive with tar, portable everywhere:\n\
+                    \x20  ```bash\n\
+                    \x20  tar -czf logs.tgz logs/\n\
+                    \x20  ```\n\
+                    2. `zip -r logs.zip logs/` — opens natively on Windows\n\
+                    3. `zip -r logs.zip logs/` — same again\n\
+                    4. Just compress it differently.";
+    let alternatives = extract_alternatives(response);
+    assert_eq!(alternatives.len(), 2);
+    assert_eq!(alternatives[0].command, "tar -czf logs.tgz logs/");
+    assert_eq!(
+        alternatives[0].tradeoff,
+        "Archive with tar, portable everywhere"
+    );
+    assert_eq!(alternatives[1].command, "zip -r logs.zip logs/");
+}
+
+#[test]
+fn prose_without_a_list_has_no_alternatives() {
+    assert!(extract_alternatives("Run `ls -la` to see hidden files.").is_empty());
+}
+
+#[test]
+fn plan_steps_keep_order_and_repeats() {
+    let response = "1. `python3 -m venv .venv` — create the virtual environment\n\
+                    2. `.venv/bin/pip install -r requirements.txt` — install dependencies\n\
+                    3. `.venv/bin/pytest` — run the tests\n\
+                    4. `.venv/bin/pytest` — run them again to catch flaky failures";
+    let steps = extract_plan(response);
+    let pairs: Vec<(&str, &str)> = steps
+        .iter()
+        .map(|step| (step.command.as_str(), step.purpose.as_str()))
+        .collect();
+    assert_eq!(
+        pairs,
+        vec![
+            ("python3 -m venv .venv", "create the virtual environment"),
+            (
+                ".venv/bin/pip install -r requirements.txt",
+                "install dependencies"
+            ),
+            (".venv/bin/pytest", "run the tests"),
+            (".venv/bin/pytest", "run them again to catch flaky failures"),
+        ]
+    );
+}
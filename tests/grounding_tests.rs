@@ -0,0 +1,74 @@
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+use tt::grounding::{Mention, ground_in, prompt_block};
+
+fn tree(paths: &[&str]) -> TempDir {
+    let dir = TempDir::new().expect("temp dir");
+    for path in paths {
+        let full = dir.path().join(path);
+        if path.ends_with('/') {
+            fs::create_dir_all(full).expect("mkdir");
+        } else {
+            fs::create_dir_all(full.parent().expect("parent")).expect("mkdir");
+            fs::write(full, "").expect("write");
+        }
+    }
+    dir
+}
+
+#[test]
+fn nouns_resolve_to_real_paths() {
+    let dir = tree(&[
+        "logs/app.log",
+        "README.md",
+        "src/main.rs",
+        "docs/readme-old.txt",
+    ]);
+    let mentions = ground_in(dir.path(), "compress the logs folder and open the readme");
+    assert_eq!(
+        mentions,
+        vec![
+            Mention {
+                term: "logs".to_string(),
+                paths: vec![PathBuf::from("logs/")],
+            },
+            Mention {
+                term: "readme".to_string(),
+                paths: vec![
+                    PathBuf::from("README.md"),
+                    PathBuf::from("docs/readme-old.txt")
+                ],
+            },
+        ]
+    );
+}
+
+#[test]
+fn gitignored_and_hidden_entries_are_skipped() {
+    let dir = tree(&[
+        ".gitignore",
+        "target/debug/report.txt",
+        ".cache/report.txt",
+        "report.md",
+    ]);
+    fs::write(dir.path().join(".gitignore"), "target/\n").expect("write");
+    let mentions = ground_in(dir.path(), "summarize report");
+    assert_eq!(mentions[0].paths, vec![PathBuf::from("report.md")]);
+}
+
+#[test]
+fn walk_depth_is_capped() {
+    let dir = tree(&["a/b/c/d/e/deepfile.txt"]);
+    assert!(ground_in(dir.path(), "open deepfile").is_empty());
+}
+
+#[test]
+fn prompt_block_lists_matches() {
+    assert_eq!(prompt_block(&[]), "");
+    let block = prompt_block(&[Mention {
+        term: "logs".to_string(),
+        paths: vec![PathBuf::from("logs/")],
+    }]);
+    assert!(block.ends_with("\n- \"logs\": `logs/`"), "{block}");
+}
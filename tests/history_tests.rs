@@ -0,0 +1,65 @@
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+use tt::history::{HistoryEntry, append_to, load_from};
+use tt::snapshot::{Snapshot, Targets};
+
+#[test]
+fn entries_round_trip_in_order() {
+    let dir = TempDir::new().expect("temp dir");
+    let path = dir.path().join("history.jsonl");
+
+    let mut first = HistoryEntry::new("list files", "model", "ls -la", "ls -la");
+    first.exit_code = Some(0);
+    let second = HistoryEntry::new("disk usage", "model", "du -sh *", "du -sh .[!.]* *");
+    append_to(&path, &first).expect("append first");
+    append_to(&path, &second).expect("append second");
+
+    let entries = load_from(&path).expect("load history");
+    assert_eq!(entries, vec![first, second]);
+    assert!(!entries[0].was_edited());
+    assert!(entries[1].was_edited());
+}
+
+#[test]
+fn missing_file_is_empty_and_bad_lines_are_skipped() {
+    let dir = TempDir::new().expect("temp dir");
+    let path = dir.path().join("history.jsonl");
+    assert!(load_from(&path).expect("load missing").is_empty());
+
+    let entry = HistoryEntry::new("q", "model", "pwd", "pwd");
+    append_to(&path, &entry).expect("append");
+    let mut contents = fs::read_to_string(&path).expect("read");
+    contents.push_str("{\"timestamp\": 1, \"quest");
+    fs::write(&path, contents).expect("write");
+
+    assert_eq!(load_from(&path).expect("load"), vec![entry]);
+}
+
+#[test]
+fn snapshots_round_trip_and_older_entries_still_load() {
+    let dir = TempDir::new().expect("temp dir");
+    let path = dir.path().join("history.jsonl");
+
+    let mut entry = HistoryEntry::new("rename", "model", "mv a b", "mv a b");
+    entry.snapshot = Some(Snapshot {
+        cwd: PathBuf::from("/work"),
+        git_head: Some("abc123".to_string()),
+        targets: vec![Targets {
+            action: "mv".to_string(),
+            files: vec![PathBuf::from("a")],
+            total: 1,
+            truncated: false,
+        }],
+        ..Snapshot::default()
+    });
+    append_to(&path, &entry).expect("append");
+    let older = r#"{"timestamp":1,"question":"q","model":"m","suggested":"ls","executed":"ls"}"#;
+    let mut contents = fs::read_to_string(&path).expect("read");
+    contents.push_str(older);
+    fs::write(&path, contents).expect("write");
+
+    let entries = load_from(&path).expect("load");
+    assert_eq!(entries[0], entry);
+    assert_eq!(entries[1].snapshot, None);
+}
@@ -0,0 +1,104 @@
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+use tt::impact::assess_in;
+
+fn tree(files: &[&str]) -> TempDir {
+    let dir = TempDir::new().expect("temp dir");
+    for file in files {
+        let path = dir.path().join(file);
+        fs::create_dir_all(path.parent().expect("parent")).expect("mkdir");
+        fs::write(path, "one\ntwo\nthree\n").expect("write");
+    }
+    dir
+}
+
+fn paths(items: &[&str]) -> Vec<PathBuf> {
+    items.iter().map(PathBuf::from).collect()
+}
+
+#[tokio::test]
+async fn globs_expand_against_the_working_directory() {
+    let dir = tree(&["a.log", "b.log", ".hidden.log", "keep.txt"]);
+    let impacts = assess_in(dir.path(), "rm -f *.log").await;
+    assert_eq!(impacts.len(), 1);
+    assert_eq!(impacts[0].action, "rm");
+    assert_eq!(impacts[0].files, paths(&["a.log", "b.log"]));
+}
+
+#[tokio::test]
+async fn quoted_patterns_and_missing_paths_stay_literal() {
+    let dir = tree(&["a.log"]);
+    let impacts = assess_in(dir.path(), "rm '*.log' gone.txt").await;
+    assert!(impacts[0].files.is_empty());
+}
+
+#[tokio::test]
+async fn recursive_removal_counts_everything_beneath() {
+    let dir = tree(&["build/out.o", "build/deep/lib.a", "src/main.rs"]);
+    let impacts = assess_in(dir.path(), "rm -rf build").await;
+    assert_eq!(impacts[0].action, "rm -r");
+    let mut files = impacts[0].files.clone();
+    files.sort();
+    assert_eq!(
+        files,
+        paths(&["build", "build/deep", "build/deep/lib.a", "build/out.o"])
+    );
+}
+
+#[tokio::test]
+async fn move_and_chmod_skip_destination_and_mode() {
+    let dir = tree(&["a.txt", "b.txt", "run.sh"]);
+    let impacts = assess_in(dir.path(), "mv {a,b}.txt archive/ && chmod -x run.sh").await;
+    assert_eq!(impacts.len(), 2);
+    assert_eq!(impacts[0].files, paths(&["a.txt", "b.txt"]));
+    assert_eq!(impacts[1].action, "chmod");
+    assert_eq!(impacts[1].files, paths(&["run.sh"]));
+}
+
+#[tokio::test]
+async fn unknowable_targets_are_skipped() {
+    let dir = tree(&["a.log"]);
+    assert!(assess_in(dir.path(), "rm \"$LOG_FILE\"").await.is_empty());
+    assert!(assess_in(dir.path(), "ls | xargs rm").await.is_empty());
+    assert!(
+        assess_in(dir.path(), "cd logs && rm a.log")
+            .await
+            .is_empty()
+    );
+    assert!(assess_in(dir.path(), "echo a.log").await.is_empty());
+}
+
+#[tokio::test]
+async fn find_runs_without_its_action() {
+    let dir = tree(&["a.tmp", "nested/b.tmp", "keep.txt"]);
+    let impacts = assess_in(dir.path(), "find . -name '*.tmp' -delete").await;
+    assert_eq!(impacts[0].action, "find -delete");
+    let mut files = impacts[0].files.clone();
+    files.sort();
+    assert_eq!(files, paths(&["a.tmp", "nested/b.tmp"]));
+    assert!(dir.path().join("a.tmp").exists());
+
+    let impacts = assess_in(dir.path(), r"find . -name '*.txt' -exec rm {} \;").await;
+    assert_eq!(impacts[0].action, "find -exec rm");
+    assert_eq!(impacts[0].files, paths(&["keep.txt"]));
+
+    let other_exec = r"find . -name '*.txt' -exec touch {} \;";
+    assert!(assess_in(dir.path(), other_exec).await.is_empty());
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn sed_in_place_shows_a_diff_without_editing() {
+    let dir = tree(&["notes.txt"]);
+    let impacts = assess_in(dir.path(), "sed -i 's/two/2/' notes.txt").await;
+    assert_eq!(impacts[0].action, "sed -i");
+    assert_eq!(impacts[0].files, paths(&["notes.txt"]));
+    let diff = &impacts[0].diffs[0];
+    assert!(diff.contains("-two"), "{diff}");
+    assert!(diff.contains("+2"), "{diff}");
+    assert_eq!(
+        fs::read_to_string(dir.path().join("notes.txt")).expect("read"),
+        "one\ntwo\nthree\n"
+    );
+}
@@ -0,0 +1,62 @@
+use std::fs;
+use tempfile::TempDir;
+use tt::lint::{Rule, lint_in};
+
+fn rules(command: &str) -> Vec<Rule> {
+    let dir = TempDir::new().expect("temp dir");
+    lint_in(dir.path(), command)
+        .into_iter()
+        .map(|finding| finding.rule)
+        .collect()
+}
+
+#[test]
+fn quoted_commands_are_clean() {
+    assert!(rules(r#"cp -- "$src" "$dest""#).is_empty());
+    assert!(rules("cd build && make").is_empty());
+    assert!(rules("for f in ./*.log; do gzip -- \"$f\"; done").is_empty());
+}
+
+#[test]
+fn unquoted_variables_are_flagged() {
+    assert_eq!(rules("echo $name"), vec![Rule::UnquotedVariable]);
+    assert_eq!(
+        rules("rm -rf $dir"),
+        vec![Rule::UnquotedVariable, Rule::MissingDoubleDash]
+    );
+    assert!(rules("OUT=$dir make").is_empty());
+}
+
+#[test]
+fn bare_paths_with_spaces_are_flagged_when_they_exist() {
+    let dir = TempDir::new().expect("temp dir");
+    fs::write(dir.path().join("My Notes.txt"), "").expect("write");
+
+    let findings = lint_in(dir.path(), "cat My Notes.txt");
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].rule, Rule::UnquotedSpacedPath);
+    assert!(findings[0].message.contains("'My Notes.txt'"));
+    assert!(lint_in(dir.path(), "cat 'My Notes.txt'").is_empty());
+    assert!(lint_in(dir.path(), "cat Other Notes.txt").is_empty());
+}
+
+#[test]
+fn ls_read_in_loops_is_flagged() {
+    assert!(rules("for f in $(ls *.txt); do wc -l -- \"$f\"; done").contains(&Rule::ParsedLs));
+    assert!(rules("ls | while read -r f; do echo \"$f\"; done").contains(&Rule::ParsedLs));
+    assert!(!rules("ls -la").contains(&Rule::ParsedLs));
+}
+
+#[test]
+fn leading_globs_need_a_double_dash() {
+    assert_eq!(rules("rm *.tmp"), vec![Rule::MissingDoubleDash]);
+    assert!(rules("rm -- *.tmp").is_empty());
+    assert!(rules("rm ./*.tmp").is_empty());
+}
+
+#[test]
+fn cd_must_be_checked_in_compound_commands() {
+    assert_eq!(rules("cd build; make"), vec![Rule::UncheckedCd]);
+    assert!(rules("cd build || exit 1; make").is_empty());
+    assert!(rules("cd build").is_empty());
+}
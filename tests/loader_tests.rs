@@ -1,5 +1,5 @@
 use std::time::Duration;
-use tt::loader::format_elapsed;
+use tt::loader::{ShimmerLoader, format_elapsed};
 
 #[test]
 fn sub_second_values_use_millis() {
@@ -15,3 +15,11 @@ fn seconds_minutes_hours_are_formatted() {
         "3h 42m 9s"
     );
 }
+
+#[tokio::test]
+async fn stopping_right_after_spawn_does_not_hang() {
+    let mut handle = ShimmerLoader::new("Working").spawn();
+    tokio::time::timeout(Duration::from_secs(2), handle.stop())
+        .await
+        .expect("loader should stop even before its first frame");
+}
@@ -0,0 +1,155 @@
+use std::fs;
+use tempfile::TempDir;
+use tt::placeholder::{Filled, PlaceholderKind, fill, find, matching_files, shell_quote};
+
+fn tokens(command: &str) -> Vec<String> {
+    find(command)
+        .into_iter()
+        .map(|placeholder| placeholder.token)
+        .collect()
+}
+
+#[test]
+fn finds_common_placeholder_styles() {
+    assert_eq!(
+        tokens("git push origin <your-branch> && git checkout <your-branch>"),
+        vec!["<your-branch>"]
+    );
+    assert_eq!(
+        tokens(r#"curl -H "Authorization: Bearer YOUR_API_KEY" {{base_url}}/v1"#),
+        vec!["YOUR_API_KEY", "{{base_url}}"]
+    );
+    assert_eq!(
+        tokens("cp /path/to/file.csv backup/ && echo API_TOKEN_HERE"),
+        vec!["/path/to/file.csv", "API_TOKEN_HERE"]
+    );
+}
+
+#[test]
+fn ignores_redirections_variables_and_ordinary_words() {
+    assert!(tokens("sort < input.txt > out.txt 2>&1").is_empty());
+    assert!(tokens("cat <<EOF\nhi\nEOF").is_empty());
+    assert!(tokens(r#"echo "$YOUR_HOME" MY_VAR=1 yourself my-app"#).is_empty());
+    assert!(tokens("ls ~/src/path/to/x").is_empty());
+}
+
+#[test]
+fn templates_and_quoted_markup_are_not_placeholders() {
+    assert!(tokens("docker ps --format '{{.Names}}'").is_empty());
+    assert!(tokens(r#"docker inspect -f "{{ .State.Status }}" web"#).is_empty());
+    assert!(tokens(r#"helm template . --set x={{ .Values.x }}"#).is_empty());
+    assert!(tokens("sed 's/<br>/\\n/g; s/<div>//' page.html").is_empty());
+    assert!(tokens(r#"grep -c "<div>" page.html"#).is_empty());
+    assert_eq!(tokens(r#"echo "<name>: YOUR_NAME""#), vec!["YOUR_NAME"]);
+}
+
+#[test]
+fn placeholders_are_classified() {
+    let kinds: Vec<PlaceholderKind> =
+        find("git push <remote> <branch-name> /path/to/dir YOUR_TOKEN")
+            .into_iter()
+            .map(|placeholder| placeholder.kind)
+            .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            PlaceholderKind::Text,
+            PlaceholderKind::Branch,
+            PlaceholderKind::Path,
+            PlaceholderKind::Secret,
+        ]
+    );
+}
+
+#[test]
+fn values_are_quoted_for_their_context() {
+    let command = r#"cp <file> "copy of YOUR_FILE" && echo 'saved YOUR_FILE'"#;
+    let values = vec![
+        ("<file>".to_string(), "it's a $file".to_string()),
+        ("YOUR_FILE".to_string(), "it's a $file".to_string()),
+    ];
+    assert_eq!(
+        fill(command, &values),
+        r#"cp 'it'\''s a $file' "copy of it's a \$file" && echo 'saved YOUR_FILE'"#
+    );
+}
+
+#[test]
+fn unchanged_values_leave_the_token() {
+    let values = vec![("<div>".to_string(), "<div>".to_string())];
+    assert_eq!(
+        fill("sed 's/<div>//' page.html", &values),
+        "sed 's/<div>//' page.html"
+    );
+}
+
+#[test]
+fn shell_quote_leaves_safe_words_bare() {
+    assert_eq!(shell_quote("feature/login-2"), "feature/login-2");
+    assert_eq!(shell_quote("two words"), "'two words'");
+    assert_eq!(shell_quote(""), "''");
+}
+
+#[test]
+fn matching_files_share_the_extension() {
+    let dir = TempDir::new().expect("temp dir");
+    for name in ["b.csv", "a.csv", "notes.txt", ".hidden.csv"] {
+        fs::write(dir.path().join(name), "").expect("write");
+    }
+    assert_eq!(
+        matching_files(dir.path(), "/path/to/data.csv"),
+        vec!["a.csv", "b.csv"]
+    );
+    assert!(matching_files(dir.path(), "/path/to/dir").is_empty());
+}
+
+#[test]
+fn secrets_stay_out_of_the_shown_command() {
+    let command = r#"curl -H "Authorization: Bearer YOUR_API_KEY" https://<host>/v1"#;
+    let values: Vec<_> = find(command)
+        .into_iter()
+        .map(|placeholder| {
+            let value = match placeholder.kind {
+                PlaceholderKind::Secret => "s3cr3t",
+                _ => "api.example.com",
+            };
+            (placeholder, value.to_string())
+        })
+        .collect();
+    let filled = Filled::new(command, &values);
+
+    assert_eq!(
+        filled.shown,
+        r#"curl -H "Authorization: Bearer YOUR_API_KEY" https://api.example.com/v1"#
+    );
+    assert_eq!(
+        filled.command(),
+        r#"curl -H "Authorization: Bearer s3cr3t" https://api.example.com/v1"#
+    );
+    assert_eq!(
+        filled.mask(r#"line 1: `curl -H "Authorization: Bearer s3cr3t" https://x'"#),
+        r#"line 1: `curl -H "Authorization: Bearer YOUR_API_KEY" https://x'"#
+    );
+
+    let edited = filled.edited(filled.shown.replace("curl", "curl -sS"));
+    assert_eq!(
+        edited.command(),
+        r#"curl -sS -H "Authorization: Bearer s3cr3t" https://api.example.com/v1"#
+    );
+}
+
+#[test]
+fn short_secrets_only_mask_the_word_they_were_filled_into() {
+    let command = "mysql --password=YOUR_PASSWORD -e 'select 1' db";
+    let values: Vec<_> = find(command)
+        .into_iter()
+        .map(|placeholder| (placeholder, "1".to_string()))
+        .collect();
+    let filled = Filled::new(command, &values);
+
+    assert_eq!(filled.command(), "mysql --password=1 -e 'select 1' db");
+    assert_eq!(
+        filled.mask("line 1: `mysql --password=1 -e 'select 1' db'"),
+        "line 1: `mysql --password=YOUR_PASSWORD -e 'select 1' db'"
+    );
+}
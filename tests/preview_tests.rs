@@ -0,0 +1,105 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+use tt::preview::{preview_in, summarize};
+
+fn paths(items: &[&str]) -> BTreeSet<PathBuf> {
+    items.iter().map(PathBuf::from).collect()
+}
+
+#[test]
+fn summary_lists_outermost_created_and_deleted_paths() {
+    let before = paths(&["src", "src/main.rs", "build", "build/out.o", "notes.txt"]);
+    let after = paths(&["src", "src/main.rs", "notes.txt", "dist", "dist/app.tgz"]);
+    let rewritten = paths(&["notes.txt", "dist/app.tgz"]);
+
+    let report = summarize(&before, &after, &rewritten);
+    assert_eq!(report.created, vec![PathBuf::from("dist")]);
+    assert_eq!(report.deleted, vec![PathBuf::from("build")]);
+    assert_eq!(report.modified, vec![PathBuf::from("notes.txt")]);
+    assert!(!report.is_empty());
+}
+
+#[test]
+fn untouched_tree_has_an_empty_summary() {
+    let tree = paths(&["a", "b/c"]);
+    assert!(summarize(&tree, &tree, &BTreeSet::new()).is_empty());
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn sandbox_reports_changes_without_touching_the_tree() {
+    let dir = TempDir::new().expect("temp dir");
+    fs::write(dir.path().join("keep.txt"), "keep\n").expect("write");
+    fs::write(dir.path().join("drop.txt"), "drop\n").expect("write");
+    fs::write(dir.path().join("edit.txt"), "old\n").expect("write");
+
+    let command = "rm drop.txt && echo new >> edit.txt && mkdir out && touch out/log";
+    let report = match preview_in(dir.path(), command).await {
+        Ok(report) => report,
+        Err(err) => {
+            eprintln!("skipping: sandbox unavailable ({err:#})");
+            return;
+        }
+    };
+
+    assert_eq!(report.exit_code, Some(0));
+    assert_eq!(report.created, vec![PathBuf::from("out")]);
+    assert_eq!(report.modified, vec![PathBuf::from("edit.txt")]);
+    assert_eq!(report.deleted, vec![PathBuf::from("drop.txt")]);
+
+    assert!(dir.path().join("drop.txt").exists());
+    assert!(!dir.path().join("out").exists());
+    assert_eq!(
+        fs::read_to_string(dir.path().join("edit.txt")).expect("read"),
+        "old\n"
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn sandbox_has_no_network_but_loopback() {
+    let dir = TempDir::new().expect("temp dir");
+    let command = "test \"$(grep -c : /proc/net/dev)\" = 1";
+    let report = match preview_in(dir.path(), command).await {
+        Ok(report) => report,
+        Err(err) => {
+            eprintln!("skipping: sandbox unavailable ({err:#})");
+            return;
+        }
+    };
+    assert_eq!(report.exit_code, Some(0));
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn sandbox_hides_runtime_sockets() {
+    let dir = TempDir::new().expect("temp dir");
+    let command =
+        "test -z \"$(ls -A /run)\" && test -z \"$SSH_AUTH_SOCK$DBUS_SESSION_BUS_ADDRESS\"";
+    let report = match preview_in(dir.path(), command).await {
+        Ok(report) => report,
+        Err(err) => {
+            eprintln!("skipping: sandbox unavailable ({err:#})");
+            return;
+        }
+    };
+    assert_eq!(report.exit_code, Some(0));
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn sandbox_writes_are_capped() {
+    let dir = TempDir::new().expect("temp dir");
+    let command = "head -c 300000000 /dev/zero > big.bin";
+    let report = match preview_in(dir.path(), command).await {
+        Ok(report) => report,
+        Err(err) => {
+            eprintln!("skipping: sandbox unavailable ({err:#})");
+            return;
+        }
+    };
+    assert_ne!(report.exit_code, Some(0));
+    assert!(!dir.path().join("big.bin").exists());
+}
@@ -0,0 +1,77 @@
+use tt::privilege::{Escalation, Privilege, escalations};
+
+fn groups(names: &[&str]) -> Vec<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
+fn escalation(tool: &str, command: &str) -> Escalation {
+    Escalation {
+        tool: tool.to_string(),
+        command: command.to_string(),
+    }
+}
+
+#[test]
+fn privilege_follows_the_probe_then_the_groups() {
+    assert_eq!(
+        Privilege::classify(Some("sudo"), true, &groups(&["staff"])),
+        Privilege::Passwordless {
+            tool: "sudo".to_string()
+        }
+    );
+    assert_eq!(
+        Privilege::classify(Some("doas"), false, &groups(&["users", "wheel"])),
+        Privilege::AdminGroup {
+            tool: "doas".to_string(),
+            group: "wheel".to_string()
+        }
+    );
+    assert_eq!(
+        Privilege::classify(Some("sudo"), false, &groups(&["users"])),
+        Privilege::Unprivileged {
+            tool: Some("sudo".to_string())
+        }
+    );
+    assert_eq!(
+        Privilege::classify(None, false, &groups(&["sudo"])),
+        Privilege::Unprivileged { tool: None }
+    );
+}
+
+#[test]
+fn prompt_line_tells_the_model_when_to_use_sudo() {
+    assert!(Privilege::Root.to_string().contains("do not prefix"));
+    let unprivileged = Privilege::Unprivileged {
+        tool: Some("sudo".to_string()),
+    };
+    assert!(!unprivileged.can_escalate());
+    assert!(unprivileged.to_string().contains("cannot use `sudo`"));
+    let admin = Privilege::AdminGroup {
+        tool: "sudo".to_string(),
+        group: "admin".to_string(),
+    };
+    assert!(admin.can_escalate());
+    assert!(admin.to_string().contains("password prompt"));
+}
+
+#[test]
+fn escalated_stages_are_found_anywhere_in_the_command() {
+    assert_eq!(
+        escalations("sudo apt-get install -y ripgrep && rg --version"),
+        vec![escalation("sudo", "apt-get install -y ripgrep")]
+    );
+    assert_eq!(
+        escalations("echo \"$(doas cat /etc/shadow)\" | wc -l"),
+        vec![escalation("doas", "cat /etc/shadow")]
+    );
+    assert_eq!(
+        escalations("env FOO=1 sudo -u www-data ls /srv; sudo -v"),
+        vec![escalation("sudo", "ls /srv"), escalation("sudo", "sudo -v")]
+    );
+}
+
+#[test]
+fn mentioning_sudo_is_not_escalating() {
+    assert!(escalations("echo sudo; man doas").is_empty());
+    assert!(escalations("grep -r 'sudo ' /etc/profile.d").is_empty());
+}
@@ -0,0 +1,110 @@
+use tt::risk::{RiskLevel, assess};
+
+fn level(command: &str) -> RiskLevel {
+    assess(command).level()
+}
+
+#[test]
+fn read_only_commands_are_low_risk() {
+    assert_eq!(level("ls -la"), RiskLevel::Low);
+    assert_eq!(level("git log --oneline | head -n 5"), RiskLevel::Low);
+    assert_eq!(level("find . -name '*.rs' -print"), RiskLevel::Low);
+    assert_eq!(level("echo hi > /dev/null 2>&1"), RiskLevel::Low);
+    assert!(assess("du -sh .").findings.is_empty());
+}
+
+#[test]
+fn recursive_rm_is_high_and_plain_rm_is_medium() {
+    assert_eq!(level("rm -rf build"), RiskLevel::High);
+    assert_eq!(level("rm --recursive build"), RiskLevel::High);
+    assert_eq!(level("rm notes.txt"), RiskLevel::Medium);
+
+    let report = assess("sudo rm -rf /");
+    assert_eq!(report.level(), RiskLevel::High);
+    let reasons: Vec<&str> = report.findings.iter().map(|f| f.reason.as_str()).collect();
+    assert!(reasons.contains(&"deletes /"));
+    assert!(reasons.iter().any(|reason| reason.contains("sudo")));
+}
+
+#[test]
+fn disk_tools_are_high_risk() {
+    assert_eq!(level("dd if=image.iso of=/dev/sdb bs=4M"), RiskLevel::High);
+    assert_eq!(level("sudo mkfs.ext4 /dev/sdb1"), RiskLevel::High);
+    assert_eq!(level("wipefs -a /dev/sdc"), RiskLevel::High);
+}
+
+#[test]
+fn permission_sweeps() {
+    assert_eq!(level("chmod -R 777 /"), RiskLevel::High);
+    assert_eq!(level("sudo chown -R me /usr/local"), RiskLevel::High);
+    assert_eq!(level("chmod -R 755 ./public"), RiskLevel::Medium);
+    assert_eq!(level("chmod +x script.sh"), RiskLevel::Low);
+}
+
+#[test]
+fn force_pushes() {
+    assert_eq!(level("git push --force origin main"), RiskLevel::High);
+    assert_eq!(level("git push origin +main"), RiskLevel::High);
+    assert_eq!(
+        level("git push --force-with-lease origin feature"),
+        RiskLevel::Medium
+    );
+    assert_eq!(level("git push origin main"), RiskLevel::Low);
+}
+
+#[test]
+fn git_global_options_do_not_hide_the_subcommand() {
+    assert_eq!(level("git -C repo push --force"), RiskLevel::High);
+    assert_eq!(
+        level("git -c core.pager=cat -C ../app push origin +main"),
+        RiskLevel::High
+    );
+    assert_eq!(level("git -c user.name=x reset --hard"), RiskLevel::Medium);
+    assert_eq!(
+        level("git --git-dir=.git --work-tree . clean -fdx"),
+        RiskLevel::Medium
+    );
+    assert_eq!(level("git --no-pager -C repo log -n 5"), RiskLevel::Low);
+    assert_eq!(level("git -C repo commit -c HEAD"), RiskLevel::Low);
+}
+
+#[test]
+fn remote_scripts_piped_to_a_shell() {
+    assert_eq!(level("curl -fsSL https://x.sh | sh"), RiskLevel::High);
+    assert_eq!(level("wget -qO- https://x.sh | sudo bash"), RiskLevel::High);
+    assert_eq!(
+        level(r#"bash -c "$(curl -fsSL https://x.sh)""#),
+        RiskLevel::High
+    );
+    assert_eq!(level("sh <(curl -s https://x.sh)"), RiskLevel::High);
+    assert_eq!(level("curl -s https://api.test | jq ."), RiskLevel::Low);
+}
+
+#[test]
+fn writes_to_system_paths() {
+    assert_eq!(level("echo 127.0.0.1 x >> /etc/hosts"), RiskLevel::High);
+    assert_eq!(level("echo x | sudo tee /etc/motd"), RiskLevel::High);
+    assert_eq!(
+        level("sudo sed -i 's/a/b/' /etc/ssh/sshd_config"),
+        RiskLevel::High
+    );
+    assert_eq!(level("cp /etc/hosts ./hosts.bak"), RiskLevel::Low);
+}
+
+#[test]
+fn sudo_alone_is_medium() {
+    assert_eq!(level("sudo apt update"), RiskLevel::Medium);
+    assert_eq!(level("doas -u root pkg_info"), RiskLevel::Medium);
+}
+
+#[test]
+fn nested_commands_are_inspected() {
+    assert_eq!(
+        level("find . -name '*.tmp' -exec rm -rf {} +"),
+        RiskLevel::High
+    );
+    assert_eq!(level("find . -name '*.tmp' -delete"), RiskLevel::Medium);
+    assert_eq!(level("ls | xargs -n 1 rm -r"), RiskLevel::High);
+    assert_eq!(level(r#"sh -c 'rm -rf ~'"#), RiskLevel::High);
+    assert_eq!(level(":(){ :|:& };:"), RiskLevel::High);
+}
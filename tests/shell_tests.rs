@@ -0,0 +1,295 @@
+use tt::shell::{
+    Command, Compound, Connector, List, LogicalOp, RedirectOp, WordPart, parse, parse_partial,
+};
+
+fn programs(source: &str) -> Vec<(Connector, String)> {
+    parse_partial(source)
+        .stages()
+        .into_iter()
+        .map(|stage| {
+            (
+                stage.connector,
+                stage.command.program().unwrap_or_default().to_string(),
+            )
+        })
+        .collect()
+}
+
+fn only_command(list: &List) -> &Command {
+    let commands = list.commands();
+    assert_eq!(list.items.len(), 1, "expected one command: {list:?}");
+    commands[0]
+}
+
+#[test]
+fn stages_follow_control_operators() {
+    assert_eq!(
+        programs("FOO=1 make && ./run || echo fail; curl x | sh & wait"),
+        vec![
+            (Connector::Start, "make".to_string()),
+            (Connector::And, "./run".to_string()),
+            (Connector::Or, "echo".to_string()),
+            (Connector::Sequence, "curl".to_string()),
+            (Connector::Pipe, "sh".to_string()),
+            (Connector::Background, "wait".to_string()),
+        ]
+    );
+
+    let list = parse("a && ! b | c || d &").expect("parse");
+    assert!(list.items[0].background);
+    let and_or = &list.items[0].and_or;
+    assert_eq!(and_or.rest.len(), 2);
+    assert_eq!(and_or.rest[0].0, LogicalOp::And);
+    assert!(and_or.rest[0].1.negated);
+    assert_eq!(and_or.rest[0].1.commands.len(), 2);
+    assert_eq!(and_or.rest[1].0, LogicalOp::Or);
+}
+
+#[test]
+fn quotes_and_escapes_are_removed_from_text() {
+    let list = parse(r#"OUT=x grep -r "a b" 'c|d' e\ f"#).expect("parse");
+    let command = list.stages()[0].command;
+    let texts: Vec<&str> = command
+        .words
+        .iter()
+        .map(|word| word.text.as_str())
+        .collect();
+    assert_eq!(texts, vec!["grep", "-r", "a b", "c|d", "e f"]);
+    assert_eq!(command.assignments[0].raw, "OUT=x");
+    assert!(command.words[2].is_quoted());
+    assert!(command.words[4].is_quoted());
+    assert!(!command.words[1].is_quoted());
+    assert_eq!(command.words[2].raw, "\"a b\"");
+}
+
+#[test]
+fn redirections_capture_fd_operator_and_target() {
+    let list = parse("cmd 2>&1 >> /var/log/x.log < input &> all").expect("parse");
+    let command = list.stages()[0].command;
+    let redirects = &command.redirects;
+    assert_eq!(command.words.len(), 1);
+    assert_eq!(redirects.len(), 4);
+    assert_eq!(redirects[0].fd, Some(2));
+    assert_eq!(redirects[0].op, RedirectOp::DupWrite);
+    assert_eq!(redirects[0].target.text, "1");
+    assert_eq!(redirects[1].op.as_str(), ">>");
+    assert_eq!(redirects[1].target.text, "/var/log/x.log");
+    assert_eq!(redirects[2].op, RedirectOp::Read);
+    assert_eq!(redirects[3].op, RedirectOp::WriteAll);
+
+    let writes: Vec<bool> = redirects
+        .iter()
+        .map(|redirect| redirect.op.writes())
+        .collect();
+    assert_eq!(writes, vec![false, true, false, true]);
+}
+
+#[test]
+fn substitutions_are_parsed_not_split() {
+    let list = parse(r#"echo "$(date +%s)" `whoami` | diff <(ls a) -"#).expect("parse");
+    assert_eq!(list.stages().len(), 2);
+
+    let sources: Vec<&str> = list
+        .substitutions()
+        .iter()
+        .map(|substitution| substitution.source.as_str())
+        .collect();
+    assert_eq!(sources, vec!["date +%s", "whoami", "ls a"]);
+
+    let nested = parse("echo $(cat $(ls -t | head -1))").expect("parse");
+    let outer = &nested.substitutions()[0].body;
+    assert_eq!(outer.stages()[0].command.program(), Some("cat"));
+    let inner = &outer.substitutions()[0].body;
+    assert!(inner.substitutions().is_empty());
+    let inner_programs: Vec<&str> = inner
+        .stages()
+        .iter()
+        .filter_map(|stage| stage.command.program())
+        .collect();
+    assert_eq!(inner_programs, vec!["ls", "head"]);
+}
+
+#[test]
+fn heredoc_bodies_are_kept_off_the_command_line() {
+    let source = "cat <<EOF > out.txt\nrm -rf /\nEOF\necho done";
+    assert_eq!(
+        programs(source),
+        vec![
+            (Connector::Start, "cat".to_string()),
+            (Connector::Sequence, "echo".to_string()),
+        ]
+    );
+
+    let list = parse(source).expect("parse");
+    let redirects = &list.stages()[0].command.redirects;
+    let heredoc = redirects[0].heredoc.as_ref().expect("heredoc");
+    assert_eq!(heredoc.body, "rm -rf /\n");
+    assert!(heredoc.expands);
+    assert_eq!(redirects[1].target.text, "out.txt");
+
+    let quoted = parse("cat <<'END'\n$HOME\nEND").expect("parse");
+    let heredoc = quoted.stages()[0].command.redirects[0].heredoc.clone();
+    assert!(!heredoc.expect("heredoc").expands);
+}
+
+#[test]
+fn groups_and_comments_separate_commands() {
+    assert_eq!(
+        programs("(cd src && ls) # list\n{ pwd; }"),
+        vec![
+            (Connector::Start, "cd".to_string()),
+            (Connector::And, "ls".to_string()),
+            (Connector::Sequence, "pwd".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn compound_commands_keep_their_structure() {
+    let list =
+        parse("if test -f a; then cat a; elif true; then :; else echo none; fi").expect("parse");
+    let Command::Compound(compound) = only_command(&list) else {
+        panic!("expected a compound command");
+    };
+    let Compound::If {
+        branches,
+        otherwise,
+    } = &compound.kind
+    else {
+        panic!("expected if: {compound:?}");
+    };
+    assert_eq!(branches.len(), 2);
+    assert!(otherwise.is_some());
+
+    let list = parse("for f in *.log; do gzip \"$f\"; done > log.txt").expect("parse");
+    let Command::Compound(compound) = only_command(&list) else {
+        panic!("expected a compound command");
+    };
+    let Compound::For {
+        variable, words, ..
+    } = &compound.kind
+    else {
+        panic!("expected for: {compound:?}");
+    };
+    assert_eq!(variable, "f");
+    assert_eq!(words.as_ref().map(Vec::len), Some(1));
+    assert_eq!(compound.redirects[0].target.text, "log.txt");
+
+    let list = parse("case $1 in start|run) go ;; *) usage ;; esac").expect("parse");
+    let Command::Compound(compound) = only_command(&list) else {
+        panic!("expected a compound command");
+    };
+    let Compound::Case { subject, arms } = &compound.kind else {
+        panic!("expected case: {compound:?}");
+    };
+    assert_eq!(subject.raw, "$1");
+    assert_eq!(arms.len(), 2);
+    assert_eq!(arms[0].patterns.len(), 2);
+
+    let list = parse("[[ -n $x && $y == a* ]] && (( n += 1 ))").expect("parse");
+    assert!(list.commands().iter().any(|command| matches!(
+        command,
+        Command::Compound(compound) if matches!(compound.kind, Compound::Test(_))
+    )));
+    assert!(list.commands().iter().any(|command| matches!(
+        command,
+        Command::Compound(compound) if matches!(compound.kind, Compound::Arithmetic(_))
+    )));
+}
+
+#[test]
+fn function_definitions_are_recognized() {
+    let list = parse("greet() { echo hi; }; function bye { echo bye; }; greet").expect("parse");
+    let names: Vec<&str> = list
+        .commands()
+        .into_iter()
+        .filter_map(|command| match command {
+            Command::Function(function) => Some(function.name.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(names, vec!["greet", "bye"]);
+    assert_eq!(
+        list.stages()
+            .last()
+            .and_then(|stage| stage.command.program()),
+        Some("greet")
+    );
+}
+
+#[test]
+fn unquoted_expansions_skip_double_quotes_and_arithmetic() {
+    let list = parse(r#"echo $a"$b"$(c)'$d'$((1 + 2))"#).expect("parse");
+    let word = &list.stages()[0].command.words[1];
+    let expansions: Vec<String> = word
+        .unquoted_expansions()
+        .into_iter()
+        .map(WordPart::text)
+        .collect();
+    assert_eq!(expansions, vec!["$a", "$(c)"]);
+    assert_eq!(word.substitutions().len(), 1);
+}
+
+#[test]
+fn syntax_errors_are_reported_but_partial_parsing_recovers() {
+    let error = parse("if true; then echo hi").expect_err("unterminated if");
+    assert!(error.to_string().contains("at byte"), "{error}");
+    assert!(parse("echo 'unterminated").is_err());
+    assert!(parse("echo hi )").is_err());
+
+    assert_eq!(
+        programs("if true; then echo hi"),
+        vec![
+            (Connector::Start, "true".to_string()),
+            (Connector::Sequence, "echo".to_string()),
+        ]
+    );
+    assert_eq!(
+        parse_partial("echo 'unterminated").stages()[0]
+            .command
+            .words[1]
+            .text,
+        "unterminated"
+    );
+}
+
+#[test]
+fn input_cut_off_after_a_backslash_or_inside_parens_does_not_panic() {
+    for source in [
+        "```\\",
+        "echo `ls \\",
+        "echo $'tab\\",
+        "((then\"fi;))!é",
+        "(( x + é",
+        "echo \"$((1 + é",
+    ] {
+        assert!(parse(source).is_err(), "{source:?} should not parse");
+        parse_partial(source);
+        tt::risk::assess(source);
+        tt::lint::lint(source);
+    }
+    assert_eq!(
+        parse_partial("echo `ls \\").substitutions()[0].source,
+        "ls \\"
+    );
+}
+
+#[test]
+fn deeply_nested_input_is_an_error_not_a_stack_overflow() {
+    let deep = 3000;
+    for source in [
+        format!("echo {}x{}", "$(".repeat(deep), ")".repeat(deep)),
+        format!("{}x{}", "( ".repeat(deep), " )".repeat(deep)),
+        format!("{}x;{}", "{ ".repeat(deep), " }".repeat(deep)),
+        format!("{}x", "f() ".repeat(deep)),
+        format!("{}x{}", "if x; then ".repeat(deep), "; fi".repeat(deep)),
+    ] {
+        let error = parse(&source).unwrap_err();
+        assert!(error.message.contains("nested too deeply"), "{error:?}");
+        parse_partial(&source);
+        tt::risk::assess(&source);
+        tt::lint::lint(&source);
+    }
+    let shallow = format!("echo {}x{}", "$(".repeat(50), ")".repeat(50));
+    assert!(parse(&shallow).is_ok());
+}
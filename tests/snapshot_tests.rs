@@ -0,0 +1,66 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use tempfile::TempDir;
+use tt::impact::{Impact, assess_in};
+use tt::snapshot::capture_in;
+
+fn git(dir: &TempDir, args: &[&str]) -> bool {
+    Command::new("git")
+        .args(["-c", "user.name=tt", "-c", "user.email=tt@example.com"])
+        .args(args)
+        .current_dir(dir.path())
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
+
+#[tokio::test]
+async fn records_targets_outside_a_repository() {
+    let dir = TempDir::new().expect("temp dir");
+    fs::write(dir.path().join("a.txt"), "a").expect("write");
+
+    let impacts = assess_in(dir.path(), "mv a.txt b.txt").await;
+    let snapshot = capture_in(dir.path(), &impacts).await;
+    assert_eq!(snapshot.cwd, dir.path());
+    assert_eq!(snapshot.git_head, None);
+    assert_eq!(snapshot.targets.len(), 1);
+    assert_eq!(snapshot.targets[0].files, vec![PathBuf::from("a.txt")]);
+}
+
+#[tokio::test]
+async fn records_git_head_branch_and_dirty_state() {
+    let dir = TempDir::new().expect("temp dir");
+    fs::write(dir.path().join("a.txt"), "a").expect("write");
+    let ready = git(&dir, &["init", "-q", "-b", "main"])
+        && git(&dir, &["add", "a.txt"])
+        && git(&dir, &["commit", "-q", "-m", "first"]);
+    if !ready {
+        eprintln!("skipping: git is unavailable");
+        return;
+    }
+
+    let snapshot = capture_in(dir.path(), &[]).await;
+    assert_eq!(snapshot.git_head.as_deref().map(str::len), Some(40));
+    assert_eq!(snapshot.git_branch.as_deref(), Some("main"));
+    assert!(!snapshot.git_dirty);
+
+    fs::write(dir.path().join("a.txt"), "changed").expect("write");
+    assert!(capture_in(dir.path(), &[]).await.git_dirty);
+}
+
+#[tokio::test]
+async fn long_listings_are_cut_down_and_keep_their_truncation() {
+    let dir = TempDir::new().expect("temp dir");
+    let impact = Impact {
+        action: "rm -r".to_string(),
+        files: (0..500).map(|i| PathBuf::from(format!("f{i}"))).collect(),
+        truncated: true,
+        diffs: Vec::new(),
+    };
+
+    let snapshot = capture_in(dir.path(), &[impact]).await;
+    let targets = &snapshot.targets[0];
+    assert_eq!(targets.files.len(), 200);
+    assert_eq!(targets.total, 500);
+    assert!(targets.truncated);
+}
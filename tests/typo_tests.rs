@@ -0,0 +1,89 @@
+use std::ffi::OsString;
+use std::fs;
+use tempfile::TempDir;
+use tt::typo::{Fix, closest, correct_in, edit_distance};
+
+fn fake_path(programs: &[&str]) -> (TempDir, OsString) {
+    let dir = TempDir::new().expect("temp dir");
+    for program in programs {
+        let path = dir.path().join(program);
+        fs::write(&path, "#!/bin/sh\n").expect("write program");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).expect("chmod");
+        }
+    }
+    let path = dir.path().as_os_str().to_owned();
+    (dir, path)
+}
+
+fn fix(typo: &str, replacement: &str) -> Fix {
+    Fix {
+        typo: typo.to_string(),
+        replacement: replacement.to_string(),
+    }
+}
+
+#[test]
+fn transpositions_count_as_one_edit() {
+    assert_eq!(edit_distance("gti", "git"), 1);
+    assert_eq!(edit_distance("stauts", "status"), 1);
+    assert_eq!(edit_distance("comit", "commit"), 1);
+    assert_eq!(edit_distance("kitten", "sitting"), 3);
+    assert_eq!(edit_distance("", "ls"), 2);
+}
+
+#[test]
+fn only_a_unique_close_match_is_confident() {
+    assert_eq!(closest("gti", ["git", "grep", "gzip"]), Some("git"));
+    assert_eq!(closest("gti", ["git", "gtk"]), None);
+    assert_eq!(closest("pyhton3", ["python3", "python"]), Some("python3"));
+    assert_eq!(closest("sl", ["ls"]), None);
+    assert_eq!(closest("dockr", ["podman"]), None);
+}
+
+#[test]
+fn misspelled_programs_are_matched_against_path() {
+    let (_dir, path) = fake_path(&["git", "grep", "make"]);
+    let correction = correct_in("gti status && make", &path).expect("correction");
+    assert_eq!(correction.command, "git status && make");
+    assert_eq!(correction.fixes, vec![fix("gti", "git")]);
+
+    assert_eq!(correct_in("zzqx --version", &path), None);
+    assert_eq!(correct_in("git status", &path), None);
+}
+
+#[test]
+fn known_subcommands_are_corrected() {
+    let (_dir, path) = fake_path(&["git", "cargo", "docker", "sudo"]);
+    assert_eq!(
+        correct_in("git comit -m 'fix comit message'", &path).map(|c| c.command),
+        Some("git commit -m 'fix comit message'".to_string())
+    );
+    assert_eq!(
+        correct_in("cargo tset --workspace", &path).map(|c| c.command),
+        Some("cargo test --workspace".to_string())
+    );
+    assert_eq!(
+        correct_in("sudo docker pss -a", &path).map(|c| c.command),
+        Some("sudo docker ps -a".to_string())
+    );
+
+    let both = correct_in("gti stauts", &path).expect("correction");
+    assert_eq!(both.command, "git status");
+    assert_eq!(both.fixes, vec![fix("gti", "git"), fix("stauts", "status")]);
+
+    let unknown_program = correct_in("zzqx --version && git stauts", &path).expect("correction");
+    assert_eq!(unknown_program.command, "zzqx --version && git status");
+    assert_eq!(unknown_program.fixes, vec![fix("stauts", "status")]);
+}
+
+#[test]
+fn plugins_options_and_unclear_subcommands_are_left_alone() {
+    let (_dir, path) = fake_path(&["git", "git-lfs", "cargo"]);
+    assert_eq!(correct_in("git lfs pull", &path), None);
+    assert_eq!(correct_in("git -C repo stats", &path), None);
+    assert_eq!(correct_in("git co main", &path), None);
+    assert_eq!(correct_in("cargo xyzzy", &path), None);
+}